@@ -1,8 +1,2451 @@
 #![no_std]
+#![allow(clippy::too_many_arguments)]
+use soroban_sdk::{
+    contract, contracterror, contractevent, contractimpl, contracttype, token, Address, BytesN,
+    Env, IntoVal, Map, String, Symbol, TryFromVal, Val, Vec,
+};
+
+pub use grant::{Breakpoint, LinearSchedule, StepSchedule, VestingSchedule};
+
+/// Contract error codes. The numeric values are part of the public interface
+/// and are documented in `ERRORS.md`; never renumber an existing variant.
+/// Codes 25 and 26 belong to the vesting wallet and must not be reused.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    /// The caller does not have the required permissions.
+    NotAuthorized = 1,
+    /// Not enough funds to complete the transaction.
+    InsufficientBalance = 2,
+    /// The grant ID does not exist in storage.
+    GrantNotFound = 3,
+    /// The grant has been paused by the admin or council.
+    GrantPaused = 4,
+    /// The amount is invalid or exceeds the remaining grant balance.
+    InvalidAmount = 5,
+    /// The grant or milestone already exists.
+    AlreadyExists = 6,
+    /// The action is blocked by an active dispute or the current state.
+    UnderDispute = 7,
+    /// The milestone ID does not exist on the grant.
+    MilestoneNotFound = 8,
+    /// The milestone has already been released.
+    AlreadyReleased = 9,
+    /// The operation does not apply to this kind of grant (milestone vs. time-vested).
+    InvalidGrantType = 10,
+    /// The vesting schedule parameters are inconsistent.
+    InvalidSchedule = 11,
+    /// The grant is not paused, so it cannot be resumed.
+    GrantNotPaused = 12,
+    /// No arbiter has been designated for the grant.
+    ArbiterNotSet = 13,
+    /// The milestone has no open dispute.
+    DisputeNotFound = 14,
+    /// The dispute already holds `MAX_EVIDENCE` evidence hashes.
+    EvidenceLimitReached = 15,
+    /// The council member has already voted for this milestone.
+    AlreadyVoted = 16,
+    /// The council has no members, duplicate members, too many members or an
+    /// unreachable threshold.
+    InvalidCouncil = 17,
+    /// The milestone is not in a state that allows this transition.
+    InvalidMilestoneState = 18,
+    /// The milestone's deadline passed before it was submitted.
+    MilestoneExpired = 19,
+    /// The grant was cancelled and can no longer change.
+    GrantCancelled = 20,
+    /// No admin handover has been proposed.
+    NoPendingAdmin = 21,
+    /// The proposed admin cannot accept before the handover delay has passed.
+    TimelockNotElapsed = 22,
+    /// The grantee has not proposed a replacement address.
+    NoPendingGranteeChange = 23,
+    /// The batch holds more than `MAX_BATCH_SIZE` items or, for grant
+    /// creation, more than `MAX_BATCH_MILESTONES` milestones.
+    BatchTooLarge = 24,
+    /// The arbiter would be the grant's admin or grantee.
+    InvalidArbiter = 27,
+}
+
+/// Version of the storage layout written by this build. `migrate` brings
+/// data written by older builds up to it after an `upgrade`.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Ledgers closed per day at the nominal 5 second close time.
+const DAY_IN_LEDGERS: u32 = 17_280;
+
+/// Entries whose TTL is at or below this many ledgers are extended when
+/// touched.
+pub const TTL_THRESHOLD: u32 = 30 * DAY_IN_LEDGERS;
+
+/// Ledgers an entry stays live after being extended, about 180 days. Grants
+/// outliving this without activity should be kept alive with `bump_grant`.
+pub const TTL_EXTEND_TO: u32 = 180 * DAY_IN_LEDGERS;
+
+/// Upper bound on council size, keeping vote tallies cheap.
+pub const MAX_COUNCIL_MEMBERS: u32 = 20;
+
+/// Upper bound on evidence hashes attached to a single dispute.
+pub const MAX_EVIDENCE: u32 = 16;
+
+/// Upper bound on items in one `create_grants_batch` or
+/// `approve_milestones_batch` call.
+///
+/// Sized for a funding round run by one program admin with a different
+/// grantee per grant: with `MAX_BATCH_MILESTONES` such a batch stays within a
+/// transaction's 100 entry footprint and 50 entry write limits. Each further
+/// admin in a batch brings about a dozen entries of its own (role, balance,
+/// index and nonce), so a batch mixing admins can exceed the limits and fail
+/// as a whole, leaving nothing written.
+pub const MAX_BATCH_SIZE: u32 = 8;
+
+/// Upper bound on milestones across all grants of one `create_grants_batch`
+/// call; each adds two footprint entries.
+pub const MAX_BATCH_MILESTONES: u32 = 12;
+
+/// Upper bound on index positions scanned by one `list_grants` call.
+pub const MAX_PAGE_SIZE: u32 = 50;
+
+/// Grant ids per stored index page.
+const INDEX_PAGE_SIZE: u32 = 64;
+
+/// Batch entrypoints fail with the contract error code
+/// `BATCH_ERROR_BASE * (index + 1) + error`, identifying the offending item
+/// and the `Error` it would have raised on its own.
+pub const BATCH_ERROR_BASE: u32 = 1_000;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataKey {
+    /// Contract administrator, allowed to upgrade and migrate. Instance storage.
+    Admin,
+    /// Storage layout version; absent for data written before versioning (1).
+    /// Instance storage.
+    SchemaVersion,
+    /// Seconds a proposed admin must wait before accepting. Instance storage.
+    AdminDelay,
+    /// Admin handover awaiting acceptance. Instance storage.
+    PendingAdmin,
+    Grant(Symbol),
+    Milestone(Symbol, Symbol),
+    Vesting(Symbol),
+    Dispute(Symbol, Symbol),
+    Council(Symbol),
+    Votes(Symbol, Symbol),
+    Submission(Symbol, Symbol),
+    /// Replacement grantee proposed with `propose_grantee_change`.
+    PendingGrantee(Symbol),
+    /// Grant admin handover started with `propose_grant_admin`.
+    PendingGrantAdmin(Symbol),
+    /// Address receiving the grant's payouts instead of the grantee.
+    Payout(Symbol),
+    /// Index pages the grant was appended to, as `(index, page)` pairs.
+    Listings(Symbol),
+    /// Newest, partially filled part of a grant index.
+    Index(GrantIndex),
+    /// Full page of a grant index, numbered from zero.
+    IndexPage(GrantIndex, u32),
+    /// Present when the account holds the role.
+    Role(Role, Address),
+}
+
+/// Permissions managed with `grant_role` and `revoke_role`.
+///
+/// Roles gate what a grant's admin may do with that grant: the admin must
+/// hold the role for the action, and holding it gives no access to grants
+/// administered by someone else. The contract admin implicitly holds every
+/// role and a `SuperAdmin` holds all the others. Only the entrypoints that
+/// take a `caller` (`pause_grant`, `resume_grant`, `start_review` and
+/// `request_changes`) also let a `SuperAdmin` act on any grant.
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Role {
+    /// Grants and revokes roles.
+    SuperAdmin,
+    /// Creates grants, manages their milestones, deadlines, council and
+    /// arbiter, and hands them over to another admin.
+    ProgramManager,
+    /// Reviews and approves milestones.
+    Reviewer,
+    /// Returns escrowed funds to the funder with `reclaim_milestone` and `cancel_grant`.
+    Treasurer,
+    /// Pauses and resumes grants.
+    Pauser,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Grant {
+    pub admin: Address,
+    pub grantee: Address,
+    pub token: Address,
+    pub total_amount: i128,
+    pub released_amount: i128,
+    /// Sum of the amounts committed to milestones, paid or not. Never exceeds
+    /// `total_amount`; the difference is free for new milestones.
+    pub allocated_amount: i128,
+    pub milestones: Vec<Symbol>,
+    /// Ledger time at which the grant was paused, if it currently is.
+    pub paused_at: Option<u64>,
+    /// Party that settles milestone disputes, set with `set_arbiter`.
+    pub arbiter: Option<Address>,
+    pub status: GrantStatus,
+}
+
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum GrantStatus {
+    Active,
+    /// Ended early with `cancel_grant`; every mutating call fails from then on.
+    Cancelled,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Milestone {
+    pub amount: i128,
+    pub description: String,
+    pub status: MilestoneStatus,
+    /// Ledger time by which the grantee must submit, set with `set_milestone_deadline`.
+    pub deadline: Option<u64>,
+}
+
+/// Review lifecycle of a milestone.
+///
+/// `Pending` -> `Submitted` (grantee) -> `UnderReview` (reviewer) ->
+/// `Approved` -> `Paid`. Reviewers may send a `Submitted` or `UnderReview`
+/// milestone back to `Pending` with `request_changes`; an arbiter may move an
+/// unpaid milestone to `Rejected` when resolving a dispute.
+///
+/// A `Pending` milestone that was never submitted reads as `Expired` once its
+/// deadline has passed; its amount can be refunded with `reclaim_milestone`,
+/// leaving it `Reclaimed`, or returned to the unallocated pool with
+/// `remove_milestone`.
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MilestoneStatus {
+    Pending,
+    Submitted,
+    UnderReview,
+    /// Approved by the admin, council or arbiter; payout follows in the same call.
+    Approved,
+    Rejected,
+    Paid,
+    Expired,
+    Reclaimed,
+}
+
+/// The grantee's latest deliverable for a milestone.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Submission {
+    /// Where the deliverable can be fetched, e.g. an IPFS or HTTPS URI.
+    pub uri: String,
+    /// Hash of the deliverable content, so reviewers can verify what was submitted.
+    pub hash: BytesN<32>,
+    pub submitted_at: u64,
+    /// Reviewer feedback from the last `request_changes`, empty if none.
+    pub feedback: String,
+}
+
+/// How an arbiter settles a disputed milestone.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DisputeResolution {
+    /// Pay the full milestone amount.
+    Approve,
+    /// Pay nothing; the milestone amount stays in escrow, unallocated.
+    Reject,
+    /// Pay the given amount, at most the milestone amount.
+    Partial(i128),
+}
+
+/// Lifecycle of a milestone dispute.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DisputeStatus {
+    Open,
+    Resolved(DisputeResolution),
+}
+
+/// A dispute over a milestone. It blocks approval while `Open`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Dispute {
+    pub opened_by: Address,
+    pub reason: String,
+    pub opened_at: u64,
+    /// Hashes of off-chain evidence submitted by either party.
+    pub evidence: Vec<BytesN<32>>,
+    pub status: DisputeStatus,
+}
+
+/// Reviewers of a grant; `threshold` of them must vote to release a milestone.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Council {
+    pub members: Vec<Address>,
+    pub threshold: u32,
+}
+
+/// A list of grants that can be enumerated with `list_grants`.
+///
+/// Grants are appended in creation order, or in the order they joined the
+/// list. `Grantee` and `Status` lists keep entries for grants that have since
+/// been rotated to another grantee or cancelled; `list_grants` skips them.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GrantIndex {
+    /// Every grant.
+    All,
+    /// Grants funded by the admin.
+    Admin(Address),
+    /// Grants currently held by the grantee.
+    Grantee(Address),
+    /// Grants currently in the status.
+    Status(GrantStatus),
+}
+
+/// Storage head of a grant index: the number of full pages, followed by the
+/// ids appended since the last one filled up.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IndexHead {
+    pub pages: u32,
+    pub tail: Vec<Symbol>,
+}
+
+/// One page of `list_grants` results.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GrantPage {
+    pub grant_ids: Vec<Symbol>,
+    /// Cursor to pass for the next page, or `None` after the last one.
+    pub next_cursor: Option<u32>,
+}
+
+/// One grant to register with `create_grants_batch`, along with its
+/// milestones.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GrantSpec {
+    pub grant_id: Symbol,
+    pub admin: Address,
+    pub grantee: Address,
+    pub token: Address,
+    pub total_amount: i128,
+    pub milestones: Vec<MilestoneSpec>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MilestoneSpec {
+    pub milestone_id: Symbol,
+    pub amount: i128,
+    pub description: String,
+}
+
+// Events. Every event's first topic after its name is the grant id, followed
+// by the milestone id for milestone-level events, so indexers can filter on
+// either.
+
+/// Published when a grant is registered.
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GrantCreated {
+    #[topic]
+    pub grant_id: Symbol,
+    pub admin: Address,
+    pub grantee: Address,
+    pub token: Address,
+    pub total_amount: i128,
+}
+
+/// Published when tokens are moved into a grant's escrow.
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Funded {
+    #[topic]
+    pub grant_id: Symbol,
+    pub from: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MilestoneAdded {
+    #[topic]
+    pub grant_id: Symbol,
+    #[topic]
+    pub milestone_id: Symbol,
+    pub amount: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MilestoneSubmitted {
+    #[topic]
+    pub grant_id: Symbol,
+    #[topic]
+    pub milestone_id: Symbol,
+    pub uri: String,
+    pub hash: BytesN<32>,
+}
+
+/// Published when a milestone is approved by the admin, the council or the
+/// arbiter, with the amount approved.
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MilestoneApproved {
+    #[topic]
+    pub grant_id: Symbol,
+    #[topic]
+    pub milestone_id: Symbol,
+    pub amount: i128,
+}
+
+/// Published when an approved milestone is paid out to the grantee.
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Released {
+    #[topic]
+    pub grant_id: Symbol,
+    #[topic]
+    pub milestone_id: Symbol,
+    pub to: Address,
+    pub amount: i128,
+}
+
+/// Published when an expired milestone's amount is refunded to the admin.
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Reclaimed {
+    #[topic]
+    pub grant_id: Symbol,
+    #[topic]
+    pub milestone_id: Symbol,
+    pub to: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MilestoneUpdated {
+    #[topic]
+    pub grant_id: Symbol,
+    #[topic]
+    pub milestone_id: Symbol,
+    pub amount: i128,
+}
+
+/// Published when a milestone is deleted, with the amount returned to the
+/// grant's unallocated pool.
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MilestoneRemoved {
+    #[topic]
+    pub grant_id: Symbol,
+    #[topic]
+    pub milestone_id: Symbol,
+    pub amount: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DeadlineSet {
+    #[topic]
+    pub grant_id: Symbol,
+    #[topic]
+    pub milestone_id: Symbol,
+    /// `None` when the deadline was removed.
+    pub deadline: Option<u64>,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReviewStarted {
+    #[topic]
+    pub grant_id: Symbol,
+    #[topic]
+    pub milestone_id: Symbol,
+    pub reviewer: Address,
+}
+
+/// Published when a reviewer sends a milestone back to the grantee.
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChangesRequested {
+    #[topic]
+    pub grant_id: Symbol,
+    #[topic]
+    pub milestone_id: Symbol,
+    pub reviewer: Address,
+    pub feedback: String,
+}
+
+/// Published for every council vote, with the number of current members who
+/// have voted so far. The vote that reaches the threshold is also followed by
+/// `MilestoneApproved` and `Released`.
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Voted {
+    #[topic]
+    pub grant_id: Symbol,
+    #[topic]
+    pub milestone_id: Symbol,
+    pub member: Address,
+    pub tally: u32,
+}
+
+/// Published when the grantee claims vested tokens of a time-vested grant.
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Claimed {
+    #[topic]
+    pub grant_id: Symbol,
+    pub to: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Paused {
+    #[topic]
+    pub grant_id: Symbol,
+    pub by: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Resumed {
+    #[topic]
+    pub grant_id: Symbol,
+    pub by: Address,
+    /// Seconds the vesting schedule was pushed back, zero if not extended.
+    pub extended_by: u64,
+}
+
+/// Published when a dispute is opened on a milestone.
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Disputed {
+    #[topic]
+    pub grant_id: Symbol,
+    #[topic]
+    pub milestone_id: Symbol,
+    pub opened_by: Address,
+    pub reason: String,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DisputeResolved {
+    #[topic]
+    pub grant_id: Symbol,
+    #[topic]
+    pub milestone_id: Symbol,
+    pub resolution: DisputeResolution,
+}
+
+/// Published when a grant is cancelled, with the final split of the escrow.
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cancelled {
+    #[topic]
+    pub grant_id: Symbol,
+    /// Amount paid to the grantee on cancellation.
+    pub paid_amount: i128,
+    /// Amount returned to the admin.
+    pub refunded_amount: i128,
+}
+
+/// Published when the admin confirms a grantee rotation.
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GranteeChanged {
+    #[topic]
+    pub grant_id: Symbol,
+    pub previous: Address,
+    pub grantee: Address,
+}
+
+/// Published when a grant admin handover is accepted.
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GrantAdminChanged {
+    #[topic]
+    pub grant_id: Symbol,
+    pub previous: Address,
+    pub admin: Address,
+}
+
+/// Published when the grantee redirects or restores the grant's payouts.
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayoutChanged {
+    #[topic]
+    pub grant_id: Symbol,
+    /// Address that receives payouts from now on.
+    pub to: Address,
+}
+
+/// An admin handover of the contract, started with `propose_admin`, or of a
+/// grant, started with `propose_grant_admin`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingAdmin {
+    pub admin: Address,
+    /// Ledger time from which `admin` may accept.
+    pub effective_at: u64,
+}
+
+/// Snapshot of a time-vested grant returned by `get_grant_info`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GrantInfo {
+    pub grantee: Address,
+    pub total_amount: i128,
+    pub claimed_amount: i128,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub schedule: VestingSchedule,
+}
 
 #[contract]
 pub struct GrantContract;
 
+/// Extend the contract instance and code along with the grant data, so a
+/// live grant never outlives the contract that holds its escrow.
+fn extend_instance_ttl(env: &Env) {
+    env.storage()
+        .instance()
+        .extend_ttl(TTL_THRESHOLD, TTL_EXTEND_TO);
+}
+
+/// Read a persistent entry, extending its TTL if it exists.
+fn read<V: TryFromVal<Env, Val>>(env: &Env, key: &DataKey) -> Option<V> {
+    let value = env.storage().persistent().get(key);
+    if value.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
+    }
+    value
+}
+
+/// Write a persistent entry and extend its TTL.
+fn write<V: IntoVal<Env, Val>>(env: &Env, key: &DataKey, value: &V) {
+    env.storage().persistent().set(key, value);
+    env.storage()
+        .persistent()
+        .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
+}
+
+fn load_grant(env: &Env, grant_id: &Symbol) -> Result<Grant, Error> {
+    extend_instance_ttl(env);
+    read(env, &DataKey::Grant(grant_id.clone())).ok_or(Error::GrantNotFound)
+}
+
+/// Load a grant that may still be changed, failing if it was cancelled.
+fn load_active_grant(env: &Env, grant_id: &Symbol) -> Result<Grant, Error> {
+    let grant = load_grant(env, grant_id)?;
+    if grant.status == GrantStatus::Cancelled {
+        return Err(Error::GrantCancelled);
+    }
+    Ok(grant)
+}
+
+fn save_grant(env: &Env, grant_id: &Symbol, grant: &Grant) {
+    extend_instance_ttl(env);
+    write(env, &DataKey::Grant(grant_id.clone()), grant);
+}
+
+/// Load a milestone, reporting it as `Expired` once its deadline has passed
+/// without a submission. A milestone sent back with `request_changes` was
+/// submitted in time and keeps its `Submission`, so it never expires.
+fn load_milestone(env: &Env, grant_id: &Symbol, milestone_id: &Symbol) -> Result<Milestone, Error> {
+    let mut milestone: Milestone = read(
+        env,
+        &DataKey::Milestone(grant_id.clone(), milestone_id.clone()),
+    )
+    .ok_or(Error::MilestoneNotFound)?;
+    if milestone.status == MilestoneStatus::Pending
+        && matches!(milestone.deadline, Some(deadline) if env.ledger().timestamp() > deadline)
+        && !env
+            .storage()
+            .persistent()
+            .has(&DataKey::Submission(grant_id.clone(), milestone_id.clone()))
+    {
+        milestone.status = MilestoneStatus::Expired;
+    }
+    Ok(milestone)
+}
+
+fn save_milestone(env: &Env, grant_id: &Symbol, milestone_id: &Symbol, milestone: &Milestone) {
+    write(
+        env,
+        &DataKey::Milestone(grant_id.clone(), milestone_id.clone()),
+        milestone,
+    );
+}
+
+fn load_vesting(env: &Env, grant_id: &Symbol) -> Result<VestingSchedule, Error> {
+    read(env, &DataKey::Vesting(grant_id.clone())).ok_or(Error::InvalidGrantType)
+}
+
+fn is_vesting(env: &Env, grant_id: &Symbol) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::Vesting(grant_id.clone()))
+}
+
+/// Validate a new grant and pull `total_amount` of `token` from `admin` into escrow.
+fn open_grant(
+    env: &Env,
+    grant_id: &Symbol,
+    admin: Address,
+    grantee: Address,
+    token: Address,
+    total_amount: i128,
+) -> Result<Grant, Error> {
+    require_role(env, Role::ProgramManager, &admin)?;
+    check_new_grant(env, grant_id, total_amount)?;
+    fund_grant(env, grant_id, admin, grantee, token, total_amount)
+}
+
+fn check_new_grant(env: &Env, grant_id: &Symbol, total_amount: i128) -> Result<(), Error> {
+    if total_amount <= 0 {
+        return Err(Error::InvalidAmount);
+    }
+    if env
+        .storage()
+        .persistent()
+        .has(&DataKey::Grant(grant_id.clone()))
+    {
+        return Err(Error::AlreadyExists);
+    }
+    Ok(())
+}
+
+/// Pull `total_amount` of `token` from `admin` into escrow and save the new grant.
+fn fund_grant(
+    env: &Env,
+    grant_id: &Symbol,
+    admin: Address,
+    grantee: Address,
+    token: Address,
+    total_amount: i128,
+) -> Result<Grant, Error> {
+    let token_client = token::Client::new(env, &token);
+    if token_client.balance(&admin) < total_amount {
+        return Err(Error::InsufficientBalance);
+    }
+    token_client.transfer(&admin, env.current_contract_address(), &total_amount);
+
+    GrantCreated {
+        grant_id: grant_id.clone(),
+        admin: admin.clone(),
+        grantee: grantee.clone(),
+        token: token.clone(),
+        total_amount,
+    }
+    .publish(env);
+    Funded {
+        grant_id: grant_id.clone(),
+        from: admin.clone(),
+        amount: total_amount,
+    }
+    .publish(env);
+
+    let grant = Grant {
+        admin,
+        grantee,
+        token,
+        total_amount,
+        released_amount: 0,
+        allocated_amount: 0,
+        milestones: Vec::new(env),
+        paused_at: None,
+        arbiter: None,
+        status: GrantStatus::Active,
+    };
+    save_grant(env, grant_id, &grant);
+    index_new_grant(env, grant_id, &grant);
+    Ok(grant)
+}
+
+/// Append `grant_id` to `index` and return the number of the page it lands
+/// on. Only the index head and, when it fills up, one new page are written.
+fn index_grant(env: &Env, index: GrantIndex, grant_id: &Symbol) -> u32 {
+    let key = DataKey::Index(index.clone());
+    let mut head = load_index_head(env, &index);
+    let page = head.pages;
+    head.tail.push_back(grant_id.clone());
+    if head.tail.len() == INDEX_PAGE_SIZE {
+        write(env, &DataKey::IndexPage(index, head.pages), &head.tail);
+        head.pages += 1;
+        head.tail = Vec::new(env);
+    }
+    write(env, &key, &head);
+    page
+}
+
+fn load_listings(env: &Env, grant_id: &Symbol) -> Vec<(GrantIndex, u32)> {
+    read(env, &DataKey::Listings(grant_id.clone())).unwrap_or(Vec::new(env))
+}
+
+/// Append an existing grant to one more index, recording where it went. A
+/// grant returning to an index it was listed on keeps its earlier entry.
+fn list_grant(env: &Env, grant_id: &Symbol, index: GrantIndex) {
+    let mut listings = load_listings(env, grant_id);
+    if listings.iter().any(|(listed, _)| listed == index) {
+        return;
+    }
+    let page = index_grant(env, index.clone(), grant_id);
+    listings.push_back((index, page));
+    write(env, &DataKey::Listings(grant_id.clone()), &listings);
+}
+
+fn load_index_head(env: &Env, index: &GrantIndex) -> IndexHead {
+    read(env, &DataKey::Index(index.clone())).unwrap_or(IndexHead {
+        pages: 0,
+        tail: Vec::new(env),
+    })
+}
+
+/// Add a grant to every index it belongs to.
+fn index_new_grant(env: &Env, grant_id: &Symbol, grant: &Grant) {
+    let mut listings = Vec::new(env);
+    for index in [
+        GrantIndex::All,
+        GrantIndex::Admin(grant.admin.clone()),
+        GrantIndex::Grantee(grant.grantee.clone()),
+        GrantIndex::Status(grant.status),
+    ] {
+        let page = index_grant(env, index.clone(), grant_id);
+        listings.push_back((index, page));
+    }
+    write(env, &DataKey::Listings(grant_id.clone()), &listings);
+}
+
+/// Whether an entry of `index` still describes the grant, as entries are
+/// not removed when a grant changes admin, grantee or status.
+fn is_listed(env: &Env, index: &GrantIndex, grant_id: &Symbol) -> bool {
+    match index {
+        GrantIndex::All => true,
+        GrantIndex::Admin(admin) => {
+            load_grant(env, grant_id).is_ok_and(|grant| grant.admin == *admin)
+        }
+        GrantIndex::Grantee(grantee) => {
+            load_grant(env, grant_id).is_ok_and(|grant| grant.grantee == *grantee)
+        }
+        GrantIndex::Status(status) => {
+            load_grant(env, grant_id).is_ok_and(|grant| grant.status == *status)
+        }
+    }
+}
+
+/// Save a new pending milestone and append it to `grant`, which the caller
+/// saves after reallocating.
+fn insert_milestone(
+    env: &Env,
+    grant_id: &Symbol,
+    grant: &mut Grant,
+    milestone_id: Symbol,
+    amount: i128,
+    description: String,
+) {
+    let milestone = Milestone {
+        amount,
+        description,
+        status: MilestoneStatus::Pending,
+        deadline: None,
+    };
+    save_milestone(env, grant_id, &milestone_id, &milestone);
+    grant.milestones.push_back(milestone_id.clone());
+    MilestoneAdded {
+        grant_id: grant_id.clone(),
+        milestone_id,
+        amount,
+    }
+    .publish(env);
+}
+
+/// Move `grant`'s allocation from `previous` to `amount`, failing if the
+/// milestones would then commit more than the grant total.
+fn reallocate(grant: &mut Grant, previous: i128, amount: i128) -> Result<(), Error> {
+    let allocated = (grant.allocated_amount - previous)
+        .checked_add(amount)
+        .ok_or(Error::InvalidAmount)?;
+    if allocated > grant.total_amount {
+        return Err(Error::InvalidAmount);
+    }
+    grant.allocated_amount = allocated;
+    Ok(())
+}
+
+fn ensure_not_paused(grant: &Grant) -> Result<(), Error> {
+    match grant.paused_at {
+        Some(_) => Err(Error::GrantPaused),
+        None => Ok(()),
+    }
+}
+
+fn load_dispute(env: &Env, grant_id: &Symbol, milestone_id: &Symbol) -> Option<Dispute> {
+    read(
+        env,
+        &DataKey::Dispute(grant_id.clone(), milestone_id.clone()),
+    )
+}
+
+fn save_dispute(env: &Env, grant_id: &Symbol, milestone_id: &Symbol, dispute: &Dispute) {
+    write(
+        env,
+        &DataKey::Dispute(grant_id.clone(), milestone_id.clone()),
+        dispute,
+    );
+}
+
+/// Load the milestone's dispute, failing unless it is still awaiting resolution.
+fn load_open_dispute(
+    env: &Env,
+    grant_id: &Symbol,
+    milestone_id: &Symbol,
+) -> Result<Dispute, Error> {
+    match load_dispute(env, grant_id, milestone_id) {
+        Some(dispute) if dispute.status == DisputeStatus::Open => Ok(dispute),
+        _ => Err(Error::DisputeNotFound),
+    }
+}
+
+/// Require `caller`'s signature and check that it is the grant's admin or grantee.
+fn authorize_party(grant: &Grant, caller: &Address) -> Result<(), Error> {
+    caller.require_auth();
+    if *caller != grant.admin && *caller != grant.grantee {
+        return Err(Error::NotAuthorized);
+    }
+    Ok(())
+}
+
+/// Mark `amount` of the milestone as paid and transfer it to the grant's
+/// payout address.
+fn release_milestone(
+    env: &Env,
+    grant_id: &Symbol,
+    grant: &mut Grant,
+    milestone_id: &Symbol,
+    milestone: &mut Milestone,
+    amount: i128,
+) -> Result<(), Error> {
+    let released = grant
+        .released_amount
+        .checked_add(amount)
+        .ok_or(Error::InvalidAmount)?;
+    if released > grant.total_amount {
+        return Err(Error::InvalidAmount);
+    }
+
+    // A partial payout returns the unpaid part of the milestone to the pool.
+    grant.allocated_amount -= milestone.amount - amount;
+    milestone.amount = amount;
+    milestone.status = MilestoneStatus::Paid;
+    grant.released_amount = released;
+    save_milestone(env, grant_id, milestone_id, milestone);
+    save_grant(env, grant_id, grant);
+    let to = pay_out(env, grant_id, grant, amount);
+    MilestoneApproved {
+        grant_id: grant_id.clone(),
+        milestone_id: milestone_id.clone(),
+        amount,
+    }
+    .publish(env);
+    Released {
+        grant_id: grant_id.clone(),
+        milestone_id: milestone_id.clone(),
+        to,
+        amount,
+    }
+    .publish(env);
+    Ok(())
+}
+
+fn load_council(env: &Env, grant_id: &Symbol) -> Option<Council> {
+    read(env, &DataKey::Council(grant_id.clone()))
+}
+
+/// Require `caller`'s signature and check that it may pause, resume or
+/// review the grant: as its admin holding `role`, as the contract admin or a
+/// `SuperAdmin`, or as a member of its council.
+fn authorize_reviewer(
+    env: &Env,
+    grant_id: &Symbol,
+    grant: &Grant,
+    caller: &Address,
+    role: Role,
+) -> Result<(), Error> {
+    caller.require_auth();
+    if is_super_admin(env, caller) || (*caller == grant.admin && has_role(env, role, caller)) {
+        return Ok(());
+    }
+    match load_council(env, grant_id) {
+        Some(council) if council.members.contains(caller) => Ok(()),
+        _ => Err(Error::NotAuthorized),
+    }
+}
+
+/// Check that the milestone may be approved now: the grant is active, the
+/// milestone has been submitted and it is not under an open dispute.
+fn ensure_approvable(
+    env: &Env,
+    grant_id: &Symbol,
+    grant: &Grant,
+    milestone_id: &Symbol,
+    milestone: &Milestone,
+) -> Result<(), Error> {
+    ensure_not_paused(grant)?;
+    match milestone.status {
+        MilestoneStatus::Submitted | MilestoneStatus::UnderReview => {}
+        MilestoneStatus::Approved | MilestoneStatus::Paid => return Err(Error::AlreadyReleased),
+        MilestoneStatus::Rejected => return Err(Error::UnderDispute),
+        MilestoneStatus::Pending => return Err(Error::InvalidMilestoneState),
+        MilestoneStatus::Expired | MilestoneStatus::Reclaimed => {
+            return Err(Error::MilestoneExpired)
+        }
+    }
+    if load_open_dispute(env, grant_id, milestone_id).is_ok() {
+        return Err(Error::UnderDispute);
+    }
+    Ok(())
+}
+
+fn load_submission(env: &Env, grant_id: &Symbol, milestone_id: &Symbol) -> Option<Submission> {
+    read(
+        env,
+        &DataKey::Submission(grant_id.clone(), milestone_id.clone()),
+    )
+}
+
+fn save_submission(env: &Env, grant_id: &Symbol, milestone_id: &Symbol, submission: &Submission) {
+    write(
+        env,
+        &DataKey::Submission(grant_id.clone(), milestone_id.clone()),
+        submission,
+    );
+}
+
+/// Address that receives the grant's payouts: the one set with
+/// `set_payout_address`, or the grantee.
+fn payout_address(env: &Env, grant_id: &Symbol, grant: &Grant) -> Address {
+    read(env, &DataKey::Payout(grant_id.clone())).unwrap_or(grant.grantee.clone())
+}
+
+/// Transfer `amount` of the grant token from escrow to the grant's payout
+/// address and return that address.
+fn pay_out(env: &Env, grant_id: &Symbol, grant: &Grant, amount: i128) -> Address {
+    let to = payout_address(env, grant_id, grant);
+    token::Client::new(env, &grant.token).transfer(&env.current_contract_address(), &to, &amount);
+    to
+}
+
+/// Vested-but-unclaimed amount of a time-vested grant at the current ledger
+/// time, or at the time it was paused while it is.
+fn claimable(env: &Env, grant: &Grant, schedule: &VestingSchedule) -> i128 {
+    let now = grant.paused_at.unwrap_or(env.ledger().timestamp());
+    schedule.vested_amount(grant.total_amount, now) - grant.released_amount
+}
+
+fn contract_admin(env: &Env) -> Address {
+    env.storage().instance().get(&DataKey::Admin).unwrap()
+}
+
+/// Whether `account` is the contract admin or a `SuperAdmin`, who hold every
+/// role.
+fn is_super_admin(env: &Env, account: &Address) -> bool {
+    *account == contract_admin(env)
+        || read::<bool>(env, &DataKey::Role(Role::SuperAdmin, account.clone())).is_some()
+}
+
+/// Whether `account` holds `role`, directly, as a `SuperAdmin` or as the
+/// contract admin.
+fn has_role(env: &Env, role: Role, account: &Address) -> bool {
+    is_super_admin(env, account)
+        || read::<bool>(env, &DataKey::Role(role, account.clone())).is_some()
+}
+
+/// Require `account`'s signature and check that it holds `role`.
+fn require_role(env: &Env, role: Role, account: &Address) -> Result<(), Error> {
+    account.require_auth();
+    if !has_role(env, role, account) {
+        return Err(Error::NotAuthorized);
+    }
+    Ok(())
+}
+
+/// `require_role` for each distinct account of a batch, as an address may
+/// only be authorized once per invocation. `authorized` collects the
+/// accounts already checked.
+fn require_role_once(
+    env: &Env,
+    role: Role,
+    account: &Address,
+    authorized: &mut Vec<Address>,
+) -> Result<(), Error> {
+    if !authorized.contains(account) {
+        require_role(env, role, account)?;
+        authorized.push_back(account.clone());
+    }
+    Ok(())
+}
+
+/// Error for item `index` of a batch failing with `error`; see `BATCH_ERROR_BASE`.
+fn batch_error(index: u32, error: Error) -> soroban_sdk::Error {
+    soroban_sdk::Error::from_contract_error(BATCH_ERROR_BASE * (index + 1) + error as u32)
+}
+
+#[contractimpl]
+impl GrantContract {
+    /// Deploy the contract with `admin` as the party allowed to upgrade it.
+    ///
+    /// `admin_delay` is the number of seconds a new admin must wait between
+    /// `propose_admin` and `accept_admin`. It is fixed for the life of the
+    /// deployment so a compromised key cannot shorten it.
+    pub fn __constructor(env: Env, admin: Address, admin_delay: u64) {
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage()
+            .instance()
+            .set(&DataKey::AdminDelay, &admin_delay);
+        env.storage()
+            .instance()
+            .set(&DataKey::SchemaVersion, &SCHEMA_VERSION);
+        extend_instance_ttl(&env);
+    }
+
+    /// Replace the contract code with the uploaded WASM `new_wasm_hash`,
+    /// keeping its address and storage. Contract admin only.
+    ///
+    /// Call `migrate` with the new code afterwards if its `SCHEMA_VERSION`
+    /// is higher.
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
+        contract_admin(&env).require_auth();
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
+    /// Bring storage written by an older build up to `SCHEMA_VERSION` after
+    /// an `upgrade`, and return the version afterwards. Contract admin only.
+    ///
+    /// Every build so far writes the first layout, so there is nothing to
+    /// convert yet; a build that changes the layout adds its conversion here.
+    pub fn migrate(env: Env) -> u32 {
+        contract_admin(&env).require_auth();
+        env.storage()
+            .instance()
+            .set(&DataKey::SchemaVersion, &SCHEMA_VERSION);
+        SCHEMA_VERSION
+    }
+
+    /// Storage layout version the contract's data is in.
+    pub fn schema_version(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::SchemaVersion)
+            .unwrap()
+    }
+
+    pub fn admin(env: Env) -> Address {
+        contract_admin(&env)
+    }
+
+    /// Start handing the contract over to `new_admin`, who can take over
+    /// with `accept_admin` once the admin delay has passed. Contract admin
+    /// only.
+    ///
+    /// A new proposal replaces any pending one and restarts the delay.
+    pub fn propose_admin(env: Env, new_admin: Address) -> PendingAdmin {
+        contract_admin(&env).require_auth();
+        let pending = PendingAdmin {
+            admin: new_admin,
+            effective_at: env
+                .ledger()
+                .timestamp()
+                .saturating_add(Self::admin_delay(env.clone())),
+        };
+        env.storage()
+            .instance()
+            .set(&DataKey::PendingAdmin, &pending);
+        pending
+    }
+
+    /// Complete a handover started with `propose_admin`. Proposed admin only.
+    pub fn accept_admin(env: Env) -> Result<(), Error> {
+        let pending: PendingAdmin = env
+            .storage()
+            .instance()
+            .get(&DataKey::PendingAdmin)
+            .ok_or(Error::NoPendingAdmin)?;
+        pending.admin.require_auth();
+        if env.ledger().timestamp() < pending.effective_at {
+            return Err(Error::TimelockNotElapsed);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::Admin, &pending.admin);
+        env.storage().instance().remove(&DataKey::PendingAdmin);
+        Ok(())
+    }
+
+    /// Withdraw a pending handover. Contract admin only.
+    pub fn cancel_admin_proposal(env: Env) -> Result<(), Error> {
+        contract_admin(&env).require_auth();
+        if !env.storage().instance().has(&DataKey::PendingAdmin) {
+            return Err(Error::NoPendingAdmin);
+        }
+        env.storage().instance().remove(&DataKey::PendingAdmin);
+        Ok(())
+    }
+
+    pub fn get_pending_admin(env: Env) -> Option<PendingAdmin> {
+        env.storage().instance().get(&DataKey::PendingAdmin)
+    }
+
+    /// Seconds a proposed admin must wait before accepting.
+    pub fn admin_delay(env: Env) -> u64 {
+        env.storage().instance().get(&DataKey::AdminDelay).unwrap()
+    }
+
+    /// Give `account` the `role`. `SuperAdmin` only.
+    pub fn grant_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), Error> {
+        require_role(&env, Role::SuperAdmin, &caller)?;
+        write(&env, &DataKey::Role(role, account), &true);
+        Ok(())
+    }
+
+    /// Take `role` away from `account`. `SuperAdmin` only.
+    ///
+    /// The contract admin keeps every role regardless.
+    pub fn revoke_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), Error> {
+        require_role(&env, Role::SuperAdmin, &caller)?;
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Role(role, account));
+        Ok(())
+    }
+
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        has_role(&env, role, &account)
+    }
+
+    /// Register a new grant of `total_amount` from `admin` to `grantee`.
+    /// `admin` must be a `ProgramManager`.
+    ///
+    /// The full amount of `token` is transferred from `admin` into the
+    /// contract, which holds it in escrow until milestones are approved.
+    pub fn create_grant(
+        env: Env,
+        grant_id: Symbol,
+        admin: Address,
+        grantee: Address,
+        token: Address,
+        total_amount: i128,
+    ) -> Result<(), Error> {
+        open_grant(&env, &grant_id, admin, grantee, token, total_amount)?;
+        Ok(())
+    }
+
+    /// Register up to `MAX_BATCH_SIZE` grants with their milestones in one
+    /// call, as `create_grant` and `add_milestone` would. Every admin must be
+    /// a `ProgramManager`.
+    ///
+    /// The whole batch is validated, including each admin's balance for the
+    /// combined amount it funds, before anything is written. If any grant is
+    /// rejected none are created, and the call fails with the code
+    /// `BATCH_ERROR_BASE * (index + 1) + error` for the first offending one.
+    pub fn create_grants_batch(env: Env, grants: Vec<GrantSpec>) -> Result<(), soroban_sdk::Error> {
+        if grants.len() > MAX_BATCH_SIZE {
+            return Err(batch_error(MAX_BATCH_SIZE, Error::BatchTooLarge));
+        }
+
+        let mut authorized = Vec::new(&env);
+        let mut grant_ids = Vec::new(&env);
+        let mut funding: Map<(Address, Address), i128> = Map::new(&env);
+        let mut milestone_count = 0;
+        for (index, spec) in grants.iter().enumerate() {
+            let fail = |error| batch_error(index as u32, error);
+            milestone_count += spec.milestones.len();
+            if milestone_count > MAX_BATCH_MILESTONES {
+                return Err(fail(Error::BatchTooLarge));
+            }
+            require_role_once(&env, Role::ProgramManager, &spec.admin, &mut authorized)
+                .map_err(fail)?;
+            check_new_grant(&env, &spec.grant_id, spec.total_amount).map_err(fail)?;
+            if grant_ids.contains(&spec.grant_id) {
+                return Err(fail(Error::AlreadyExists));
+            }
+            grant_ids.push_back(spec.grant_id.clone());
+
+            let mut milestone_ids = Vec::new(&env);
+            let mut allocated: i128 = 0;
+            for milestone in spec.milestones.iter() {
+                if milestone.amount <= 0 {
+                    return Err(fail(Error::InvalidAmount));
+                }
+                if milestone_ids.contains(&milestone.milestone_id) {
+                    return Err(fail(Error::AlreadyExists));
+                }
+                milestone_ids.push_back(milestone.milestone_id);
+                allocated = allocated
+                    .checked_add(milestone.amount)
+                    .filter(|allocated| *allocated <= spec.total_amount)
+                    .ok_or(fail(Error::InvalidAmount))?;
+            }
+
+            let source = (spec.admin.clone(), spec.token.clone());
+            let needed = funding
+                .get(source.clone())
+                .unwrap_or(0)
+                .checked_add(spec.total_amount)
+                .ok_or(fail(Error::InvalidAmount))?;
+            if token::Client::new(&env, &spec.token).balance(&spec.admin) < needed {
+                return Err(fail(Error::InsufficientBalance));
+            }
+            funding.set(source, needed);
+        }
+
+        for (index, spec) in grants.iter().enumerate() {
+            let fail = |error| batch_error(index as u32, error);
+            let mut grant = fund_grant(
+                &env,
+                &spec.grant_id,
+                spec.admin,
+                spec.grantee,
+                spec.token,
+                spec.total_amount,
+            )
+            .map_err(fail)?;
+            for milestone in spec.milestones.iter() {
+                reallocate(&mut grant, 0, milestone.amount).map_err(fail)?;
+                insert_milestone(
+                    &env,
+                    &spec.grant_id,
+                    &mut grant,
+                    milestone.milestone_id,
+                    milestone.amount,
+                    milestone.description,
+                );
+            }
+            save_grant(&env, &spec.grant_id, &grant);
+        }
+        Ok(())
+    }
+
+    /// Register a time-vested grant that streams `total_amount` linearly to
+    /// `grantee` over `duration` seconds starting at the current ledger time.
+    ///
+    /// Nothing is claimable during the first `cliff` seconds; at the cliff the
+    /// amount accrued so far unlocks at once. `cliff` must not exceed `duration`.
+    ///
+    /// Funds are escrowed exactly as for `create_grant`; the grantee withdraws
+    /// the vested portion with `claim`.
+    pub fn initialize_grant(
+        env: Env,
+        grant_id: Symbol,
+        admin: Address,
+        grantee: Address,
+        token: Address,
+        total_amount: i128,
+        duration: u64,
+        cliff: u64,
+    ) -> Result<(), Error> {
+        if cliff > duration {
+            return Err(Error::InvalidSchedule);
+        }
+        let start_time = env.ledger().timestamp();
+        let end_time = start_time
+            .checked_add(duration)
+            .ok_or(Error::InvalidSchedule)?;
+        let schedule = VestingSchedule::Linear(LinearSchedule {
+            start_time,
+            cliff_time: start_time + cliff,
+            end_time,
+        });
+        Self::initialize_scheduled_grant(
+            env,
+            grant_id,
+            admin,
+            grantee,
+            token,
+            total_amount,
+            schedule,
+        )
+    }
+
+    /// Register a time-vested grant that releases `total_amount` according to
+    /// an explicit `schedule` (linear, piecewise-linear or periodic steps).
+    ///
+    /// The schedule is validated against `total_amount` before any funds move.
+    pub fn initialize_scheduled_grant(
+        env: Env,
+        grant_id: Symbol,
+        admin: Address,
+        grantee: Address,
+        token: Address,
+        total_amount: i128,
+        schedule: VestingSchedule,
+    ) -> Result<(), Error> {
+        if !schedule.is_valid(total_amount) {
+            return Err(Error::InvalidSchedule);
+        }
+        open_grant(&env, &grant_id, admin, grantee, token, total_amount)?;
+        write(&env, &DataKey::Vesting(grant_id), &schedule);
+        Ok(())
+    }
+
+    /// Attach a milestone worth `amount` to an existing grant. Grant admin,
+    /// as a `ProgramManager`.
+    ///
+    /// Fails with `InvalidAmount` if the grant's milestones would then commit
+    /// more than its total.
+    pub fn add_milestone(
+        env: Env,
+        grant_id: Symbol,
+        milestone_id: Symbol,
+        amount: i128,
+        description: String,
+    ) -> Result<(), Error> {
+        let mut grant = load_active_grant(&env, &grant_id)?;
+        require_role(&env, Role::ProgramManager, &grant.admin)?;
+        if is_vesting(&env, &grant_id) {
+            return Err(Error::InvalidGrantType);
+        }
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        let key = DataKey::Milestone(grant_id.clone(), milestone_id.clone());
+        if env.storage().persistent().has(&key) {
+            return Err(Error::AlreadyExists);
+        }
+        reallocate(&mut grant, 0, amount)?;
+
+        insert_milestone(
+            &env,
+            &grant_id,
+            &mut grant,
+            milestone_id,
+            amount,
+            description,
+        );
+        save_grant(&env, &grant_id, &grant);
+        Ok(())
+    }
+
+    /// Change the amount and description of a milestone the grantee has not
+    /// submitted yet. Grant admin, as a `ProgramManager`.
+    ///
+    /// The grant allocation is rebalanced; raising the amount fails with
+    /// `InvalidAmount` if it would commit more than the grant total.
+    pub fn update_milestone(
+        env: Env,
+        grant_id: Symbol,
+        milestone_id: Symbol,
+        amount: i128,
+        description: String,
+    ) -> Result<(), Error> {
+        let mut grant = load_active_grant(&env, &grant_id)?;
+        require_role(&env, Role::ProgramManager, &grant.admin)?;
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        let mut milestone = load_milestone(&env, &grant_id, &milestone_id)?;
+        match milestone.status {
+            MilestoneStatus::Pending => {}
+            MilestoneStatus::Expired | MilestoneStatus::Reclaimed => {
+                return Err(Error::MilestoneExpired)
+            }
+            _ => return Err(Error::InvalidMilestoneState),
+        }
+        if load_open_dispute(&env, &grant_id, &milestone_id).is_ok() {
+            return Err(Error::UnderDispute);
+        }
+        reallocate(&mut grant, milestone.amount, amount)?;
+
+        milestone.amount = amount;
+        milestone.description = description;
+        save_milestone(&env, &grant_id, &milestone_id, &milestone);
+        save_grant(&env, &grant_id, &grant);
+        MilestoneUpdated {
+            grant_id,
+            milestone_id,
+            amount,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Delete a milestone that was never submitted, or that expired, and
+    /// return its amount to the grant's unallocated pool. Grant admin, as a
+    /// `ProgramManager`.
+    pub fn remove_milestone(env: Env, grant_id: Symbol, milestone_id: Symbol) -> Result<(), Error> {
+        let mut grant = load_active_grant(&env, &grant_id)?;
+        require_role(&env, Role::ProgramManager, &grant.admin)?;
+        let milestone = load_milestone(&env, &grant_id, &milestone_id)?;
+        if !matches!(
+            milestone.status,
+            MilestoneStatus::Pending | MilestoneStatus::Expired
+        ) {
+            return Err(Error::InvalidMilestoneState);
+        }
+        if load_open_dispute(&env, &grant_id, &milestone_id).is_ok() {
+            return Err(Error::UnderDispute);
+        }
+
+        reallocate(&mut grant, milestone.amount, 0)?;
+        if let Some(index) = grant.milestones.first_index_of(&milestone_id) {
+            grant.milestones.remove(index);
+        }
+        let storage = env.storage().persistent();
+        storage.remove(&DataKey::Milestone(grant_id.clone(), milestone_id.clone()));
+        storage.remove(&DataKey::Submission(grant_id.clone(), milestone_id.clone()));
+        storage.remove(&DataKey::Votes(grant_id.clone(), milestone_id.clone()));
+        storage.remove(&DataKey::Dispute(grant_id.clone(), milestone_id.clone()));
+        save_grant(&env, &grant_id, &grant);
+        MilestoneRemoved {
+            grant_id,
+            milestone_id,
+            amount: milestone.amount,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Approve a milestone and release its amount to the grantee. Grant
+    /// admin, as a `Reviewer`.
+    ///
+    /// Grants with a council must use `vote_milestone` instead.
+    pub fn approve_milestone(
+        env: Env,
+        grant_id: Symbol,
+        milestone_id: Symbol,
+    ) -> Result<(), Error> {
+        let mut grant = load_active_grant(&env, &grant_id)?;
+        require_role(&env, Role::Reviewer, &grant.admin)?;
+        if load_council(&env, &grant_id).is_some() {
+            return Err(Error::NotAuthorized);
+        }
+        let mut milestone = load_milestone(&env, &grant_id, &milestone_id)?;
+        ensure_approvable(&env, &grant_id, &grant, &milestone_id, &milestone)?;
+
+        milestone.status = MilestoneStatus::Approved;
+        let amount = milestone.amount;
+        release_milestone(
+            &env,
+            &grant_id,
+            &mut grant,
+            &milestone_id,
+            &mut milestone,
+            amount,
+        )
+    }
+
+    /// Approve up to `MAX_BATCH_SIZE` milestones, given as
+    /// `(grant_id, milestone_id)` pairs, and release each to its grant's
+    /// payout address, as `approve_milestone` would.
+    ///
+    /// Every milestone is checked before any is paid. If one cannot be
+    /// approved none are, and the call fails with the code
+    /// `BATCH_ERROR_BASE * (index + 1) + error` for the first offending one.
+    pub fn approve_milestones_batch(
+        env: Env,
+        milestones: Vec<(Symbol, Symbol)>,
+    ) -> Result<(), soroban_sdk::Error> {
+        if milestones.len() > MAX_BATCH_SIZE {
+            return Err(batch_error(MAX_BATCH_SIZE, Error::BatchTooLarge));
+        }
+
+        let mut authorized = Vec::new(&env);
+        for (index, item) in milestones.iter().enumerate() {
+            let fail = |error| batch_error(index as u32, error);
+            let (grant_id, milestone_id) = item.clone();
+            let grant = load_active_grant(&env, &grant_id).map_err(fail)?;
+            require_role_once(&env, Role::Reviewer, &grant.admin, &mut authorized).map_err(fail)?;
+            if load_council(&env, &grant_id).is_some() {
+                return Err(fail(Error::NotAuthorized));
+            }
+            let milestone = load_milestone(&env, &grant_id, &milestone_id).map_err(fail)?;
+            ensure_approvable(&env, &grant_id, &grant, &milestone_id, &milestone).map_err(fail)?;
+            if milestones.first_index_of(&item) != Some(index as u32) {
+                return Err(fail(Error::AlreadyReleased));
+            }
+        }
+
+        for (index, (grant_id, milestone_id)) in milestones.iter().enumerate() {
+            let fail = |error| batch_error(index as u32, error);
+            // Reload per item: earlier items may have paid from the same grant.
+            let mut grant = load_grant(&env, &grant_id).map_err(fail)?;
+            let mut milestone = load_milestone(&env, &grant_id, &milestone_id).map_err(fail)?;
+            milestone.status = MilestoneStatus::Approved;
+            let amount = milestone.amount;
+            release_milestone(
+                &env,
+                &grant_id,
+                &mut grant,
+                &milestone_id,
+                &mut milestone,
+                amount,
+            )
+            .map_err(fail)?;
+        }
+        Ok(())
+    }
+
+    /// Require the grantee to submit the milestone by `deadline`, or remove
+    /// the deadline with `None`. Grant admin, as a `ProgramManager`.
+    ///
+    /// Only milestones that are still open and not under an open dispute may
+    /// be rescheduled; an expired milestone cannot be revived.
+    pub fn set_milestone_deadline(
+        env: Env,
+        grant_id: Symbol,
+        milestone_id: Symbol,
+        deadline: Option<u64>,
+    ) -> Result<(), Error> {
+        let grant = load_active_grant(&env, &grant_id)?;
+        require_role(&env, Role::ProgramManager, &grant.admin)?;
+        let mut milestone = load_milestone(&env, &grant_id, &milestone_id)?;
+        match milestone.status {
+            MilestoneStatus::Pending
+            | MilestoneStatus::Submitted
+            | MilestoneStatus::UnderReview => {}
+            MilestoneStatus::Expired | MilestoneStatus::Reclaimed => {
+                return Err(Error::MilestoneExpired)
+            }
+            _ => return Err(Error::InvalidMilestoneState),
+        }
+        if load_open_dispute(&env, &grant_id, &milestone_id).is_ok() {
+            return Err(Error::UnderDispute);
+        }
+
+        milestone.deadline = deadline;
+        save_milestone(&env, &grant_id, &milestone_id, &milestone);
+        DeadlineSet {
+            grant_id,
+            milestone_id,
+            deadline,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Refund an expired milestone's amount from escrow to the admin and
+    /// shrink the grant total accordingly. Grant admin, as a `Treasurer`.
+    ///
+    /// Returns the amount refunded. To keep the amount in the grant for new
+    /// milestones instead, use `remove_milestone`.
+    pub fn reclaim_milestone(
+        env: Env,
+        grant_id: Symbol,
+        milestone_id: Symbol,
+    ) -> Result<i128, Error> {
+        let mut grant = load_active_grant(&env, &grant_id)?;
+        require_role(&env, Role::Treasurer, &grant.admin)?;
+        let mut milestone = load_milestone(&env, &grant_id, &milestone_id)?;
+        if milestone.status != MilestoneStatus::Expired {
+            return Err(Error::InvalidMilestoneState);
+        }
+        if load_open_dispute(&env, &grant_id, &milestone_id).is_ok() {
+            return Err(Error::UnderDispute);
+        }
+
+        let amount = milestone.amount;
+        milestone.status = MilestoneStatus::Reclaimed;
+        grant.total_amount -= amount;
+        grant.allocated_amount -= amount;
+        save_milestone(&env, &grant_id, &milestone_id, &milestone);
+        save_grant(&env, &grant_id, &grant);
+        token::Client::new(&env, &grant.token).transfer(
+            &env.current_contract_address(),
+            &grant.admin,
+            &amount,
+        );
+        Reclaimed {
+            grant_id,
+            milestone_id,
+            to: grant.admin,
+            amount,
+        }
+        .publish(&env);
+        Ok(amount)
+    }
+
+    /// Submit a deliverable for review. Grantee only.
+    ///
+    /// Valid from `Pending`, including after a reviewer requested changes;
+    /// resubmitting replaces the previous deliverable.
+    pub fn submit_milestone(
+        env: Env,
+        grant_id: Symbol,
+        milestone_id: Symbol,
+        uri: String,
+        hash: BytesN<32>,
+    ) -> Result<(), Error> {
+        let grant = load_active_grant(&env, &grant_id)?;
+        grant.grantee.require_auth();
+        let mut milestone = load_milestone(&env, &grant_id, &milestone_id)?;
+        match milestone.status {
+            MilestoneStatus::Pending => {}
+            MilestoneStatus::Expired | MilestoneStatus::Reclaimed => {
+                return Err(Error::MilestoneExpired)
+            }
+            _ => return Err(Error::InvalidMilestoneState),
+        }
+
+        let submission = Submission {
+            uri: uri.clone(),
+            hash: hash.clone(),
+            submitted_at: env.ledger().timestamp(),
+            feedback: String::from_str(&env, ""),
+        };
+        save_submission(&env, &grant_id, &milestone_id, &submission);
+        milestone.status = MilestoneStatus::Submitted;
+        save_milestone(&env, &grant_id, &milestone_id, &milestone);
+        MilestoneSubmitted {
+            grant_id,
+            milestone_id,
+            uri,
+            hash,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Mark a submitted milestone as being reviewed. Grant admin, as a
+    /// `Reviewer`, or council member.
+    pub fn start_review(
+        env: Env,
+        grant_id: Symbol,
+        milestone_id: Symbol,
+        reviewer: Address,
+    ) -> Result<(), Error> {
+        let grant = load_active_grant(&env, &grant_id)?;
+        authorize_reviewer(&env, &grant_id, &grant, &reviewer, Role::Reviewer)?;
+        let mut milestone = load_milestone(&env, &grant_id, &milestone_id)?;
+        if milestone.status != MilestoneStatus::Submitted {
+            return Err(Error::InvalidMilestoneState);
+        }
+
+        milestone.status = MilestoneStatus::UnderReview;
+        save_milestone(&env, &grant_id, &milestone_id, &milestone);
+        ReviewStarted {
+            grant_id,
+            milestone_id,
+            reviewer,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Send a submitted milestone back to the grantee with `feedback`. Grant
+    /// admin, as a `Reviewer`, or council member.
+    ///
+    /// The milestone returns to `Pending` and any council votes are cleared,
+    /// so the revised deliverable is reviewed afresh.
+    pub fn request_changes(
+        env: Env,
+        grant_id: Symbol,
+        milestone_id: Symbol,
+        reviewer: Address,
+        feedback: String,
+    ) -> Result<(), Error> {
+        let grant = load_active_grant(&env, &grant_id)?;
+        authorize_reviewer(&env, &grant_id, &grant, &reviewer, Role::Reviewer)?;
+        let mut milestone = load_milestone(&env, &grant_id, &milestone_id)?;
+        if !matches!(
+            milestone.status,
+            MilestoneStatus::Submitted | MilestoneStatus::UnderReview
+        ) {
+            return Err(Error::InvalidMilestoneState);
+        }
+
+        if let Some(mut submission) = load_submission(&env, &grant_id, &milestone_id) {
+            submission.feedback = feedback.clone();
+            save_submission(&env, &grant_id, &milestone_id, &submission);
+        }
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Votes(grant_id.clone(), milestone_id.clone()));
+        milestone.status = MilestoneStatus::Pending;
+        save_milestone(&env, &grant_id, &milestone_id, &milestone);
+        ChangesRequested {
+            grant_id,
+            milestone_id,
+            reviewer,
+            feedback,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    pub fn get_submission(
+        env: Env,
+        grant_id: Symbol,
+        milestone_id: Symbol,
+    ) -> Result<Submission, Error> {
+        load_submission(&env, &grant_id, &milestone_id).ok_or(Error::MilestoneNotFound)
+    }
+
+    /// Require `threshold` of `members` to vote before a milestone is paid,
+    /// replacing single-admin approval. Grant admin, as a `ProgramManager`.
+    ///
+    /// Reconfiguring the council keeps existing votes, but only votes from
+    /// current members count toward the threshold.
+    pub fn set_council(
+        env: Env,
+        grant_id: Symbol,
+        members: Vec<Address>,
+        threshold: u32,
+    ) -> Result<(), Error> {
+        let grant = load_active_grant(&env, &grant_id)?;
+        require_role(&env, Role::ProgramManager, &grant.admin)?;
+        if members.is_empty()
+            || members.len() > MAX_COUNCIL_MEMBERS
+            || threshold == 0
+            || threshold > members.len()
+        {
+            return Err(Error::InvalidCouncil);
+        }
+        for (i, member) in members.iter().enumerate() {
+            if members.first_index_of(&member) != Some(i as u32) {
+                return Err(Error::InvalidCouncil);
+            }
+        }
+
+        let council = Council { members, threshold };
+        write(&env, &DataKey::Council(grant_id), &council);
+        Ok(())
+    }
+
+    /// Record `member`'s approval of a milestone. Once `threshold` current
+    /// council members have voted, the milestone is released automatically.
+    ///
+    /// Returns `true` if this vote released the milestone.
+    pub fn vote_milestone(
+        env: Env,
+        grant_id: Symbol,
+        milestone_id: Symbol,
+        member: Address,
+    ) -> Result<bool, Error> {
+        member.require_auth();
+        let mut grant = load_active_grant(&env, &grant_id)?;
+        let council = load_council(&env, &grant_id).ok_or(Error::NotAuthorized)?;
+        if !council.members.contains(&member) {
+            return Err(Error::NotAuthorized);
+        }
+        let mut milestone = load_milestone(&env, &grant_id, &milestone_id)?;
+        ensure_approvable(&env, &grant_id, &grant, &milestone_id, &milestone)?;
+
+        let key = DataKey::Votes(grant_id.clone(), milestone_id.clone());
+        let mut votes: Vec<Address> = read(&env, &key).unwrap_or(Vec::new(&env));
+        if votes.contains(&member) {
+            return Err(Error::AlreadyVoted);
+        }
+        votes.push_back(member.clone());
+        write(&env, &key, &votes);
+
+        let tally = votes
+            .iter()
+            .filter(|voter| council.members.contains(voter))
+            .count() as u32;
+        Voted {
+            grant_id: grant_id.clone(),
+            milestone_id: milestone_id.clone(),
+            member,
+            tally,
+        }
+        .publish(&env);
+        if tally < council.threshold {
+            return Ok(false);
+        }
+        milestone.status = MilestoneStatus::Approved;
+        let amount = milestone.amount;
+        release_milestone(
+            &env,
+            &grant_id,
+            &mut grant,
+            &milestone_id,
+            &mut milestone,
+            amount,
+        )?;
+        Ok(true)
+    }
+
+    pub fn get_council(env: Env, grant_id: Symbol) -> Option<Council> {
+        load_council(&env, &grant_id)
+    }
+
+    /// Council members who have voted for the milestone so far.
+    pub fn get_votes(env: Env, grant_id: Symbol, milestone_id: Symbol) -> Vec<Address> {
+        read(&env, &DataKey::Votes(grant_id, milestone_id)).unwrap_or(Vec::new(&env))
+    }
+
+    /// Designate the party that resolves milestone disputes. Grant admin, as
+    /// a `ProgramManager`.
+    ///
+    /// The arbiter cannot be the grant's admin or grantee, and cannot be
+    /// replaced while any of the grant's disputes is open.
+    pub fn set_arbiter(env: Env, grant_id: Symbol, arbiter: Address) -> Result<(), Error> {
+        let mut grant = load_active_grant(&env, &grant_id)?;
+        require_role(&env, Role::ProgramManager, &grant.admin)?;
+        if arbiter == grant.admin || arbiter == grant.grantee {
+            return Err(Error::InvalidArbiter);
+        }
+        for milestone_id in grant.milestones.iter() {
+            if load_open_dispute(&env, &grant_id, &milestone_id).is_ok() {
+                return Err(Error::UnderDispute);
+            }
+        }
+        grant.arbiter = Some(arbiter);
+        save_grant(&env, &grant_id, &grant);
+        Ok(())
+    }
+
+    /// Open a dispute on an unpaid milestone, blocking its approval until the
+    /// arbiter resolves it. Callable by the grant admin or grantee.
+    pub fn open_dispute(
+        env: Env,
+        grant_id: Symbol,
+        milestone_id: Symbol,
+        caller: Address,
+        reason: String,
+    ) -> Result<(), Error> {
+        let grant = load_active_grant(&env, &grant_id)?;
+        authorize_party(&grant, &caller)?;
+        if grant.arbiter.is_none() {
+            return Err(Error::ArbiterNotSet);
+        }
+        let milestone = load_milestone(&env, &grant_id, &milestone_id)?;
+        match milestone.status {
+            MilestoneStatus::Paid => return Err(Error::AlreadyReleased),
+            MilestoneStatus::Expired | MilestoneStatus::Reclaimed => {
+                return Err(Error::MilestoneExpired)
+            }
+            _ => {}
+        }
+        if load_dispute(&env, &grant_id, &milestone_id).is_some() {
+            return Err(Error::AlreadyExists);
+        }
+
+        let dispute = Dispute {
+            opened_by: caller.clone(),
+            reason: reason.clone(),
+            opened_at: env.ledger().timestamp(),
+            evidence: Vec::new(&env),
+            status: DisputeStatus::Open,
+        };
+        save_dispute(&env, &grant_id, &milestone_id, &dispute);
+        Disputed {
+            grant_id,
+            milestone_id,
+            opened_by: caller,
+            reason,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Attach the hash of a piece of evidence to an open dispute. Callable by
+    /// the grant admin or grantee.
+    pub fn add_evidence(
+        env: Env,
+        grant_id: Symbol,
+        milestone_id: Symbol,
+        caller: Address,
+        evidence_hash: BytesN<32>,
+    ) -> Result<(), Error> {
+        let grant = load_active_grant(&env, &grant_id)?;
+        authorize_party(&grant, &caller)?;
+        let mut dispute = load_open_dispute(&env, &grant_id, &milestone_id)?;
+        if dispute.evidence.len() >= MAX_EVIDENCE {
+            return Err(Error::EvidenceLimitReached);
+        }
+
+        dispute.evidence.push_back(evidence_hash);
+        save_dispute(&env, &grant_id, &milestone_id, &dispute);
+        Ok(())
+    }
+
+    /// Settle an open dispute. Arbiter only.
+    ///
+    /// `Approve` pays the full milestone, `Partial` pays the given amount and
+    /// closes the milestone at that value, and `Reject` pays nothing and
+    /// prevents the milestone from ever being approved.
+    pub fn resolve_dispute(
+        env: Env,
+        grant_id: Symbol,
+        milestone_id: Symbol,
+        resolution: DisputeResolution,
+    ) -> Result<(), Error> {
+        let mut grant = load_active_grant(&env, &grant_id)?;
+        let arbiter = grant.arbiter.clone().ok_or(Error::ArbiterNotSet)?;
+        arbiter.require_auth();
+        let mut dispute = load_open_dispute(&env, &grant_id, &milestone_id)?;
+        let mut milestone = load_milestone(&env, &grant_id, &milestone_id)?;
+
+        match resolution {
+            DisputeResolution::Approve | DisputeResolution::Partial(_) => {
+                ensure_not_paused(&grant)?;
+                if matches!(
+                    milestone.status,
+                    MilestoneStatus::Expired | MilestoneStatus::Reclaimed
+                ) {
+                    return Err(Error::MilestoneExpired);
+                }
+                let amount = match resolution {
+                    DisputeResolution::Partial(amount) => amount,
+                    _ => milestone.amount,
+                };
+                if amount <= 0 || amount > milestone.amount {
+                    return Err(Error::InvalidAmount);
+                }
+                milestone.status = MilestoneStatus::Approved;
+                release_milestone(
+                    &env,
+                    &grant_id,
+                    &mut grant,
+                    &milestone_id,
+                    &mut milestone,
+                    amount,
+                )?;
+            }
+            DisputeResolution::Reject => {
+                milestone.status = MilestoneStatus::Rejected;
+                reallocate(&mut grant, milestone.amount, 0)?;
+                save_milestone(&env, &grant_id, &milestone_id, &milestone);
+                save_grant(&env, &grant_id, &grant);
+            }
+        }
+
+        dispute.status = DisputeStatus::Resolved(resolution.clone());
+        save_dispute(&env, &grant_id, &milestone_id, &dispute);
+        DisputeResolved {
+            grant_id,
+            milestone_id,
+            resolution,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    pub fn get_dispute(env: Env, grant_id: Symbol, milestone_id: Symbol) -> Result<Dispute, Error> {
+        load_dispute(&env, &grant_id, &milestone_id).ok_or(Error::DisputeNotFound)
+    }
+
+    /// Amount of a time-vested grant that the grantee can claim right now.
+    /// While the grant is paused, this is what had vested when it was paused.
+    pub fn claimable_balance(env: Env, grant_id: Symbol) -> Result<i128, Error> {
+        let grant = load_grant(&env, &grant_id)?;
+        let schedule = load_vesting(&env, &grant_id)?;
+        if grant.status == GrantStatus::Cancelled {
+            return Ok(0);
+        }
+        Ok(claimable(&env, &grant, &schedule))
+    }
+
+    /// Withdraw everything vested since the last claim to the grant's payout
+    /// address. Grantee only.
+    ///
+    /// Returns the amount transferred, which is zero when nothing new has vested.
+    pub fn claim(env: Env, grant_id: Symbol) -> Result<i128, Error> {
+        let mut grant = load_active_grant(&env, &grant_id)?;
+        grant.grantee.require_auth();
+        ensure_not_paused(&grant)?;
+        let schedule = load_vesting(&env, &grant_id)?;
+
+        let amount = claimable(&env, &grant, &schedule);
+        if amount > 0 {
+            grant.released_amount += amount;
+            save_grant(&env, &grant_id, &grant);
+            let to = pay_out(&env, &grant_id, &grant, amount);
+            Claimed {
+                grant_id,
+                to,
+                amount,
+            }
+            .publish(&env);
+        }
+        Ok(amount)
+    }
+
+    /// Suspend the grant, blocking milestone approvals and claims until it is
+    /// resumed. Grant admin, as a `Pauser`, or council member.
+    pub fn pause_grant(env: Env, grant_id: Symbol, caller: Address) -> Result<(), Error> {
+        let mut grant = load_active_grant(&env, &grant_id)?;
+        authorize_reviewer(&env, &grant_id, &grant, &caller, Role::Pauser)?;
+        ensure_not_paused(&grant)?;
+
+        grant.paused_at = Some(env.ledger().timestamp());
+        save_grant(&env, &grant_id, &grant);
+        Paused {
+            grant_id,
+            by: caller,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Lift a pause placed with `pause_grant`. Same callers as `pause_grant`.
+    ///
+    /// When `extend_vesting` is set on a time-vested grant, its schedule is
+    /// pushed back by the time spent paused, so nothing accrues during the
+    /// suspension and the vesting end moves out by the same amount.
+    pub fn resume_grant(
+        env: Env,
+        grant_id: Symbol,
+        caller: Address,
+        extend_vesting: bool,
+    ) -> Result<(), Error> {
+        let mut grant = load_active_grant(&env, &grant_id)?;
+        authorize_reviewer(&env, &grant_id, &grant, &caller, Role::Pauser)?;
+        let paused_at = grant.paused_at.ok_or(Error::GrantNotPaused)?;
+
+        let mut extended_by = 0;
+        if extend_vesting && is_vesting(&env, &grant_id) {
+            extended_by = env.ledger().timestamp() - paused_at;
+            let schedule = load_vesting(&env, &grant_id)?
+                .delayed(extended_by)
+                .ok_or(Error::InvalidSchedule)?;
+            write(&env, &DataKey::Vesting(grant_id.clone()), &schedule);
+        }
+        grant.paused_at = None;
+        save_grant(&env, &grant_id, &grant);
+        Resumed {
+            grant_id,
+            by: caller,
+            extended_by,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// End the grant early. Grant admin, as a `Treasurer`.
+    ///
+    /// The grantee receives what it is owed at this point: the vested but
+    /// unclaimed amount of a time-vested grant (as of the pause, if paused),
+    /// or nothing more for a milestone grant, whose approved milestones are
+    /// already paid. The rest of the escrow is refunded to the admin and the
+    /// grant is marked `Cancelled`. Returns the amount paid to the grantee.
+    pub fn cancel_grant(env: Env, grant_id: Symbol) -> Result<i128, Error> {
+        let mut grant = load_active_grant(&env, &grant_id)?;
+        require_role(&env, Role::Treasurer, &grant.admin)?;
+
+        let paid = match load_vesting(&env, &grant_id) {
+            Ok(schedule) => claimable(&env, &grant, &schedule),
+            Err(_) => 0,
+        };
+        let refunded = grant.total_amount - grant.released_amount - paid;
+
+        grant.released_amount += paid;
+        grant.total_amount = grant.released_amount;
+        grant.allocated_amount = grant.released_amount;
+        grant.status = GrantStatus::Cancelled;
+        save_grant(&env, &grant_id, &grant);
+        list_grant(&env, &grant_id, GrantIndex::Status(GrantStatus::Cancelled));
+        if paid > 0 {
+            pay_out(&env, &grant_id, &grant, paid);
+        }
+        if refunded > 0 {
+            token::Client::new(&env, &grant.token).transfer(
+                &env.current_contract_address(),
+                &grant.admin,
+                &refunded,
+            );
+        }
+        Cancelled {
+            grant_id,
+            paid_amount: paid,
+            refunded_amount: refunded,
+        }
+        .publish(&env);
+        Ok(paid)
+    }
+
+    /// Extend the TTL of every storage entry belonging to the grant, the
+    /// index pages listing it and the contract instance. Callable by anyone.
+    ///
+    /// Touching a grant through any other entrypoint already extends the
+    /// entries it reads or writes; this keeps idle long-running grants, and
+    /// milestones nobody has looked at, from being archived.
+    pub fn bump_grant(env: Env, grant_id: Symbol) -> Result<(), Error> {
+        let grant = load_grant(&env, &grant_id)?;
+        let _: Option<VestingSchedule> = read(&env, &DataKey::Vesting(grant_id.clone()));
+        let _: Option<Council> = read(&env, &DataKey::Council(grant_id.clone()));
+        let _: Option<Address> = read(&env, &DataKey::PendingGrantee(grant_id.clone()));
+        let _: Option<PendingAdmin> = read(&env, &DataKey::PendingGrantAdmin(grant_id.clone()));
+        let _: Option<Address> = read(&env, &DataKey::Payout(grant_id.clone()));
+        for (index, page) in load_listings(&env, &grant_id).iter() {
+            if page < load_index_head(&env, &index).pages {
+                let _: Option<Vec<Symbol>> = read(&env, &DataKey::IndexPage(index, page));
+            }
+        }
+        for milestone_id in grant.milestones.iter() {
+            let _: Option<Milestone> = read(
+                &env,
+                &DataKey::Milestone(grant_id.clone(), milestone_id.clone()),
+            );
+            let _: Option<Submission> = read(
+                &env,
+                &DataKey::Submission(grant_id.clone(), milestone_id.clone()),
+            );
+            let _: Option<Vec<Address>> = read(
+                &env,
+                &DataKey::Votes(grant_id.clone(), milestone_id.clone()),
+            );
+            let _: Option<Dispute> = read(&env, &DataKey::Dispute(grant_id.clone(), milestone_id));
+        }
+        Ok(())
+    }
+
+    /// Ask to move the grant to `new_grantee`, for example after a key loss
+    /// or a move to a multisig. Current grantee only.
+    ///
+    /// Takes effect once the grant admin calls `confirm_grantee_change`. A new
+    /// proposal replaces any pending one.
+    pub fn propose_grantee_change(
+        env: Env,
+        grant_id: Symbol,
+        new_grantee: Address,
+    ) -> Result<(), Error> {
+        let grant = load_active_grant(&env, &grant_id)?;
+        grant.grantee.require_auth();
+        write(&env, &DataKey::PendingGrantee(grant_id), &new_grantee);
+        Ok(())
+    }
+
+    /// Hand the grant over to the grantee proposed with
+    /// `propose_grantee_change`. Grant admin, as a `ProgramManager`, only.
+    ///
+    /// Any payout address set by the previous grantee is cleared, so payouts
+    /// go to the new grantee until it sets its own. The grant's arbiter
+    /// cannot become its grantee.
+    pub fn confirm_grantee_change(env: Env, grant_id: Symbol) -> Result<(), Error> {
+        let mut grant = load_active_grant(&env, &grant_id)?;
+        require_role(&env, Role::ProgramManager, &grant.admin)?;
+        let new_grantee: Address = read(&env, &DataKey::PendingGrantee(grant_id.clone()))
+            .ok_or(Error::NoPendingGranteeChange)?;
+        if grant.arbiter.as_ref() == Some(&new_grantee) {
+            return Err(Error::InvalidArbiter);
+        }
+
+        let previous = grant.grantee;
+        grant.grantee = new_grantee.clone();
+        save_grant(&env, &grant_id, &grant);
+        let storage = env.storage().persistent();
+        storage.remove(&DataKey::PendingGrantee(grant_id.clone()));
+        storage.remove(&DataKey::Payout(grant_id.clone()));
+        list_grant(&env, &grant_id, GrantIndex::Grantee(new_grantee.clone()));
+        GranteeChanged {
+            grant_id,
+            previous,
+            grantee: new_grantee,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    pub fn get_pending_grantee(env: Env, grant_id: Symbol) -> Option<Address> {
+        read(&env, &DataKey::PendingGrantee(grant_id))
+    }
+
+    /// Start handing the grant over to `new_admin`, who can take over with
+    /// `accept_grant_admin` once the contract's admin delay has passed. Grant
+    /// admin, as a `ProgramManager`.
+    ///
+    /// A new proposal replaces any pending one and restarts the delay.
+    pub fn propose_grant_admin(
+        env: Env,
+        grant_id: Symbol,
+        new_admin: Address,
+    ) -> Result<PendingAdmin, Error> {
+        let grant = load_active_grant(&env, &grant_id)?;
+        require_role(&env, Role::ProgramManager, &grant.admin)?;
+        let pending = PendingAdmin {
+            admin: new_admin,
+            effective_at: env
+                .ledger()
+                .timestamp()
+                .saturating_add(Self::admin_delay(env.clone())),
+        };
+        write(&env, &DataKey::PendingGrantAdmin(grant_id), &pending);
+        Ok(pending)
+    }
+
+    /// Complete a handover started with `propose_grant_admin`. Proposed
+    /// admin only, who must be a `ProgramManager` and cannot be the grant's
+    /// arbiter.
+    ///
+    /// The new admin takes over the grant's milestones and receives any
+    /// funds later reclaimed from it.
+    pub fn accept_grant_admin(env: Env, grant_id: Symbol) -> Result<(), Error> {
+        let mut grant = load_active_grant(&env, &grant_id)?;
+        let key = DataKey::PendingGrantAdmin(grant_id.clone());
+        let pending: PendingAdmin = read(&env, &key).ok_or(Error::NoPendingAdmin)?;
+        require_role(&env, Role::ProgramManager, &pending.admin)?;
+        if env.ledger().timestamp() < pending.effective_at {
+            return Err(Error::TimelockNotElapsed);
+        }
+        if grant.arbiter.as_ref() == Some(&pending.admin) {
+            return Err(Error::InvalidArbiter);
+        }
+
+        let previous = grant.admin;
+        grant.admin = pending.admin.clone();
+        save_grant(&env, &grant_id, &grant);
+        env.storage().persistent().remove(&key);
+        list_grant(&env, &grant_id, GrantIndex::Admin(pending.admin.clone()));
+        GrantAdminChanged {
+            grant_id,
+            previous,
+            admin: pending.admin,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Withdraw a pending grant admin handover. Grant admin only.
+    pub fn cancel_grant_admin_proposal(env: Env, grant_id: Symbol) -> Result<(), Error> {
+        let grant = load_grant(&env, &grant_id)?;
+        grant.admin.require_auth();
+        let key = DataKey::PendingGrantAdmin(grant_id);
+        if read::<PendingAdmin>(&env, &key).is_none() {
+            return Err(Error::NoPendingAdmin);
+        }
+        env.storage().persistent().remove(&key);
+        Ok(())
+    }
+
+    pub fn get_pending_grant_admin(env: Env, grant_id: Symbol) -> Option<PendingAdmin> {
+        read(&env, &DataKey::PendingGrantAdmin(grant_id))
+    }
+
+    /// Send the grant's milestone releases, vesting claims and cancellation
+    /// settlement to `payout` instead of the grantee, or back to the grantee
+    /// when `None`. Grantee only.
+    pub fn set_payout_address(
+        env: Env,
+        grant_id: Symbol,
+        payout: Option<Address>,
+    ) -> Result<(), Error> {
+        let grant = load_active_grant(&env, &grant_id)?;
+        grant.grantee.require_auth();
+        let key = DataKey::Payout(grant_id.clone());
+        let to = match payout {
+            Some(payout) => {
+                write(&env, &key, &payout);
+                payout
+            }
+            None => {
+                env.storage().persistent().remove(&key);
+                grant.grantee
+            }
+        };
+        PayoutChanged { grant_id, to }.publish(&env);
+        Ok(())
+    }
+
+    /// Address the grant's payouts are sent to.
+    pub fn get_payout_address(env: Env, grant_id: Symbol) -> Result<Address, Error> {
+        let grant = load_grant(&env, &grant_id)?;
+        Ok(payout_address(&env, &grant_id, &grant))
+    }
+
+    pub fn get_grant_info(env: Env, grant_id: Symbol) -> Result<GrantInfo, Error> {
+        let grant = load_grant(&env, &grant_id)?;
+        let schedule = load_vesting(&env, &grant_id)?;
+        Ok(GrantInfo {
+            grantee: grant.grantee,
+            total_amount: grant.total_amount,
+            claimed_amount: grant.released_amount,
+            start_time: schedule.start_time(),
+            end_time: schedule.end_time(),
+            schedule,
+        })
+    }
+
+    pub fn get_grant(env: Env, grant_id: Symbol) -> Result<Grant, Error> {
+        load_grant(&env, &grant_id)
+    }
+
+    pub fn get_milestone(
+        env: Env,
+        grant_id: Symbol,
+        milestone_id: Symbol,
+    ) -> Result<Milestone, Error> {
+        load_milestone(&env, &grant_id, &milestone_id)
+    }
+
+    /// Amount of the grant not yet released to the grantee.
+    pub fn get_remaining_amount(env: Env, grant_id: Symbol) -> Result<i128, Error> {
+        let grant = load_grant(&env, &grant_id)?;
+        Ok(grant.total_amount - grant.released_amount)
+    }
+
+    /// Page through `index`, scanning up to `limit` positions (capped at
+    /// `MAX_PAGE_SIZE`, which is also used for 0) from `cursor`; pass 0 for
+    /// the first page and then each page's `next_cursor`.
+    ///
+    /// A page can hold fewer ids than positions scanned, since grants that
+    /// have left a `Grantee` or `Status` index are skipped, so keep going
+    /// until `next_cursor` is `None`.
+    pub fn list_grants(env: Env, index: GrantIndex, cursor: u32, limit: u32) -> GrantPage {
+        let head = load_index_head(&env, &index);
+        let len = head.pages * INDEX_PAGE_SIZE + head.tail.len();
+        let limit = if limit == 0 {
+            MAX_PAGE_SIZE
+        } else {
+            limit.min(MAX_PAGE_SIZE)
+        };
+        let end = cursor.saturating_add(limit).min(len);
+
+        let mut grant_ids = Vec::new(&env);
+        let mut position = cursor;
+        while position < end {
+            let page = position / INDEX_PAGE_SIZE;
+            let ids = if page < head.pages {
+                read(&env, &DataKey::IndexPage(index.clone(), page)).unwrap_or(Vec::new(&env))
+            } else {
+                head.tail.clone()
+            };
+            let page_start = page * INDEX_PAGE_SIZE;
+            let page_end = end.min(page_start + INDEX_PAGE_SIZE);
+            for grant_id in ids
+                .slice(position - page_start..page_end - page_start)
+                .iter()
+            {
+                if is_listed(&env, &index, &grant_id) {
+                    grant_ids.push_back(grant_id);
+                }
+            }
+            position = page_end;
+        }
+        GrantPage {
+            grant_ids,
+            next_cursor: (end < len).then_some(end),
+        }
+    }
+
+    /// Amount of the grant not yet committed to any milestone.
+    pub fn get_unallocated_amount(env: Env, grant_id: Symbol) -> Result<i128, Error> {
+        let grant = load_grant(&env, &grant_id)?;
+        Ok(grant.total_amount - grant.allocated_amount)
     }
 }
 
@@ -10,6 +2453,219 @@ mod test;
 
 // Grant math utilities used by tests and (optionally) the contract.
 pub mod grant {
+    use soroban_sdk::{contracttype, Vec};
+
+    /// Straight-line vesting from `start_time` to `end_time` with nothing
+    /// claimable before `cliff_time`.
+    #[contracttype]
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub struct LinearSchedule {
+        pub start_time: u64,
+        pub cliff_time: u64,
+        pub end_time: u64,
+    }
+
+    /// A point on a piecewise-linear curve: `amount` has vested in total by `time`.
+    #[contracttype]
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub struct Breakpoint {
+        pub time: u64,
+        pub amount: i128,
+    }
+
+    /// Equal tranches unlocking at the end of each of `periods` intervals of
+    /// `period` seconds, counted from `start_time`.
+    #[contracttype]
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub struct StepSchedule {
+        pub start_time: u64,
+        pub period: u64,
+        pub periods: u32,
+    }
+
+    /// How a time-vested grant releases its total over time.
+    #[contracttype]
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub enum VestingSchedule {
+        Linear(LinearSchedule),
+        /// Cumulative breakpoints, interpolated linearly between neighbours.
+        /// Nothing is vested before the first breakpoint and its amount unlocks
+        /// at once when it is reached.
+        Piecewise(Vec<Breakpoint>),
+        Step(StepSchedule),
+    }
+
+    /// 30-day step period for "monthly" tranches.
+    pub const MONTH: u64 = 30 * 24 * 60 * 60;
+    /// 90-day step period for "quarterly" tranches.
+    pub const QUARTER: u64 = 3 * MONTH;
+
+    impl VestingSchedule {
+        /// Check that the schedule is well-formed for a grant of `total`.
+        ///
+        /// Linear schedules need `start <= cliff <= end`. Piecewise schedules
+        /// need strictly increasing times, non-decreasing non-negative amounts
+        /// and a final amount equal to `total`. Step schedules need a non-zero
+        /// period and count whose end time fits in a `u64`.
+        pub fn is_valid(&self, total: i128) -> bool {
+            if total <= 0 {
+                return false;
+            }
+            match self {
+                VestingSchedule::Linear(s) => {
+                    s.start_time <= s.cliff_time && s.cliff_time <= s.end_time
+                }
+                VestingSchedule::Piecewise(points) => {
+                    let mut previous: Option<Breakpoint> = None;
+                    for point in points.iter() {
+                        if point.amount < 0 {
+                            return false;
+                        }
+                        if let Some(prev) = &previous {
+                            if point.time <= prev.time || point.amount < prev.amount {
+                                return false;
+                            }
+                        }
+                        previous = Some(point);
+                    }
+                    matches!(previous, Some(last) if last.amount == total)
+                }
+                VestingSchedule::Step(s) => {
+                    s.period > 0
+                        && s.periods > 0
+                        && s.period
+                            .checked_mul(s.periods as u64)
+                            .and_then(|span| s.start_time.checked_add(span))
+                            .is_some()
+                }
+            }
+        }
+
+        /// Time at which vesting begins.
+        pub fn start_time(&self) -> u64 {
+            match self {
+                VestingSchedule::Linear(s) => s.start_time,
+                VestingSchedule::Piecewise(points) => {
+                    points.first().map(|p| p.time).unwrap_or_default()
+                }
+                VestingSchedule::Step(s) => s.start_time,
+            }
+        }
+
+        /// Time at which the full total has vested.
+        pub fn end_time(&self) -> u64 {
+            match self {
+                VestingSchedule::Linear(s) => s.end_time,
+                VestingSchedule::Piecewise(points) => {
+                    points.last().map(|p| p.time).unwrap_or_default()
+                }
+                VestingSchedule::Step(s) => s.start_time + s.period * s.periods as u64,
+            }
+        }
+
+        /// Cumulative amount of `total` vested at `now`.
+        ///
+        /// Assumes the schedule passed [`VestingSchedule::is_valid`]. Like
+        /// [`compute_claimable_balance`], the result is exact, never exceeds
+        /// `total` and is monotonic in `now`.
+        pub fn vested_amount(&self, total: i128, now: u64) -> i128 {
+            let total = total as u128;
+            let vested = match self {
+                VestingSchedule::Linear(s) => compute_claimable_balance_with_cliff(
+                    total,
+                    s.start_time,
+                    now,
+                    s.end_time - s.start_time,
+                    s.cliff_time - s.start_time,
+                ),
+                VestingSchedule::Piecewise(points) => compute_piecewise_balance(points, now),
+                VestingSchedule::Step(s) => {
+                    compute_step_balance(total, s.start_time, now, s.period, s.periods)
+                }
+            };
+            vested as i128
+        }
+
+        /// Shift every point of the schedule `by` seconds later, or `None` if a
+        /// timestamp would overflow.
+        ///
+        /// The shifted schedule vests at `t + by` exactly what the original
+        /// vested at `t`, which is how pauses are excluded from accrual.
+        pub fn delayed(&self, by: u64) -> Option<VestingSchedule> {
+            Some(match self {
+                VestingSchedule::Linear(s) => VestingSchedule::Linear(LinearSchedule {
+                    start_time: s.start_time.checked_add(by)?,
+                    cliff_time: s.cliff_time.checked_add(by)?,
+                    end_time: s.end_time.checked_add(by)?,
+                }),
+                VestingSchedule::Piecewise(points) => {
+                    let mut shifted = Vec::new(points.env());
+                    for point in points.iter() {
+                        shifted.push_back(Breakpoint {
+                            time: point.time.checked_add(by)?,
+                            amount: point.amount,
+                        });
+                    }
+                    VestingSchedule::Piecewise(shifted)
+                }
+                VestingSchedule::Step(s) => {
+                    let delayed = StepSchedule {
+                        start_time: s.start_time.checked_add(by)?,
+                        period: s.period,
+                        periods: s.periods,
+                    };
+                    // The end time must still fit in a u64.
+                    delayed
+                        .period
+                        .checked_mul(delayed.periods as u64)?
+                        .checked_add(delayed.start_time)?;
+                    VestingSchedule::Step(delayed)
+                }
+            })
+        }
+    }
+
+    /// Evaluate a validated piecewise-linear curve at `now`.
+    pub fn compute_piecewise_balance(points: &Vec<Breakpoint>, now: u64) -> u128 {
+        let mut previous: Option<Breakpoint> = None;
+        for point in points.iter() {
+            if now < point.time {
+                return match previous {
+                    None => 0,
+                    Some(prev) => {
+                        let rise = (point.amount - prev.amount) as u128;
+                        prev.amount as u128
+                            + mul_div(
+                                rise,
+                                (now - prev.time) as u128,
+                                (point.time - prev.time) as u128,
+                            )
+                    }
+                };
+            }
+            previous = Some(point);
+        }
+        previous.map(|p| p.amount as u128).unwrap_or_default()
+    }
+
+    /// Compute the balance of a step schedule that unlocks a share of `total`
+    /// at the end of each `period`. After `k` periods `total * k / periods`,
+    /// rounded down, is unlocked, so step sizes differ by at most one unit
+    /// and the last step brings the balance to exactly `total`.
+    pub fn compute_step_balance(
+        total: u128,
+        start: u64,
+        now: u64,
+        period: u64,
+        periods: u32,
+    ) -> u128 {
+        if now <= start || period == 0 || periods == 0 {
+            return 0;
+        }
+        let elapsed_periods = ((now - start) / period).min(periods as u64);
+        mul_div(total, elapsed_periods as u128, periods as u128)
+    }
+
     /// Compute the claimable balance for a linear vesting grant.
     ///
     /// - `total`: total amount granted (u128)
@@ -17,7 +2673,14 @@ pub mod grant {
     /// - `now`: current timestamp (seconds, u64)
     /// - `duration`: grant duration (seconds, u64)
     ///
-    /// Returns the amount (u128) claimable at `now` (clamped 0..=total).
+    /// Returns `floor(total * (now - start) / duration)` clamped to `0..=total`.
+    /// The result is exact for every input: the product is formed in 256 bits
+    /// (see [`mul_div`]), so large totals never vest early.
+    ///
+    /// The function is monotonic in `now`: for fixed `total`, `start` and
+    /// `duration`, `now_a <= now_b` implies the result at `now_a` is at most the
+    /// result at `now_b`. Callers may therefore pay out the difference between
+    /// two observations without it ever going negative.
     pub fn compute_claimable_balance(total: u128, start: u64, now: u64, duration: u64) -> u128 {
         if duration == 0 {
             return if now >= start { total } else { 0 };
@@ -25,33 +2688,80 @@ pub mod grant {
         if now <= start {
             return 0;
         }
-        let elapsed = now.saturating_sub(start);
+        let elapsed = now - start;
         if elapsed >= duration {
             return total;
         }
+        mul_div(total, elapsed as u128, duration as u128)
+    }
 
-        // Use decomposition to reduce risk of intermediate overflow:
-        // total * elapsed / duration == (total / duration) * elapsed + (total % duration) * elapsed / duration
-        let dur = duration as u128;
-        let el = elapsed as u128;
-        let whole = total / dur;
-        let rem = total % dur;
-
-        // whole * el shouldn't overflow in realistic token amounts, but use checked_mul with fallback.
-        let part1 = match whole.checked_mul(el) {
-            Some(v) => v,
-            None => {
-                // fallback: perform (whole / dur) * (el * dur) approximated by dividing early
-                // This branch is extremely unlikely; clamp to total as safe fallback.
-                return total;
+    /// Like [`compute_claimable_balance`], but nothing is claimable before
+    /// `start + cliff`.
+    ///
+    /// Once the cliff passes, the amount accrued since `start` unlocks at once
+    /// and linear vesting continues to `start + duration`. A `cliff` of zero is
+    /// identical to [`compute_claimable_balance`], and the result stays
+    /// monotonic in `now`.
+    pub fn compute_claimable_balance_with_cliff(
+        total: u128,
+        start: u64,
+        now: u64,
+        duration: u64,
+        cliff: u64,
+    ) -> u128 {
+        match start.checked_add(cliff) {
+            Some(cliff_end) if now >= cliff_end => {
+                compute_claimable_balance(total, start, now, duration)
             }
-        };
-        let part2 = match rem.checked_mul(el) {
-            Some(v) => v / dur,
-            None => {
-                return total;
+            _ => 0,
+        }
+    }
+
+    /// Compute `floor(a * b / d)` without intermediate overflow.
+    ///
+    /// The caller must ensure `d != 0` and that the quotient fits in a `u128`,
+    /// which holds whenever `b <= d`.
+    pub fn mul_div(a: u128, b: u128, d: u128) -> u128 {
+        if let Some(product) = a.checked_mul(b) {
+            return product / d;
+        }
+        let (hi, lo) = mul_wide(a, b);
+        div_wide(hi, lo, d)
+    }
+
+    /// Full 256-bit product of two `u128` values as `(high, low)` halves.
+    fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+        const MASK: u128 = u64::MAX as u128;
+        let (a_hi, a_lo) = (a >> 64, a & MASK);
+        let (b_hi, b_lo) = (b >> 64, b & MASK);
+
+        let lo_lo = a_lo * b_lo;
+        let hi_lo = a_hi * b_lo;
+        let lo_hi = a_lo * b_hi;
+        let hi_hi = a_hi * b_hi;
+
+        // Each middle-column term is below 2^64, so their sum cannot overflow and
+        // its upper bits carry into the high half.
+        let mid = (lo_lo >> 64) + (hi_lo & MASK) + (lo_hi & MASK);
+        let lo = (mid << 64) | (lo_lo & MASK);
+        let hi = hi_hi + (hi_lo >> 64) + (lo_hi >> 64) + (mid >> 64);
+        (hi, lo)
+    }
+
+    /// Divide the 256-bit value `hi:lo` by `d`, assuming the quotient fits in 128 bits (`hi < d`).
+    fn div_wide(hi: u128, lo: u128, d: u128) -> u128 {
+        let mut rem = hi;
+        let mut quot: u128 = 0;
+        for i in (0..128).rev() {
+            // Shift the next bit of `lo` into the remainder. If the remainder's top
+            // bit is set the shifted value exceeds 2^128 > d, so it always divides.
+            let carry = rem >> 127;
+            rem = (rem << 1) | ((lo >> i) & 1);
+            if carry == 1 || rem >= d {
+                rem = rem.wrapping_sub(d);
+                quot |= 1 << i;
             }
-        };
-        part1 + part2
+        }
+        quot
     }
 }
@@ -1,58 +1,197 @@
 #![cfg(test)]
 
 use super::*;
+use soroban_sdk::{
+    testutils::{
+        storage::{Instance as _, Persistent as _},
+        Address as _, Events as _, Ledger,
+    },
+    token, vec, xdr, Address, BytesN, Env, Event, String, Symbol, Vec,
+};
+
+const ADMIN_BALANCE: i128 = 10_000_000_000;
+const ADMIN_DELAY: u64 = 86_400;
+
+/// Registers the contract and a Stellar asset, funding `admin` with
+/// `ADMIN_BALANCE` so grants can be escrowed.
+fn setup(env: &Env) -> (GrantContractClient<'_>, Address, Address, Address) {
+    env.mock_all_auths();
+    let admin = Address::generate(env);
+    let grantee = Address::generate(env);
+    let issuer = Address::generate(env);
+    let token = env.register_stellar_asset_contract_v2(issuer).address();
+    token::StellarAssetClient::new(env, &token).mint(&admin, &ADMIN_BALANCE);
+    let contract_id = env.register(GrantContract, (&admin, &ADMIN_DELAY));
+    let client = GrantContractClient::new(env, &contract_id);
+    (client, admin, grantee, token)
+}
+
+/// Submits a placeholder deliverable so the milestone can be approved.
+fn submit(client: &GrantContractClient, env: &Env, grant_id: &Symbol, milestone_id: &Symbol) {
+    client.submit_milestone(
+        grant_id,
+        milestone_id,
+        &String::from_str(env, "ipfs://deliverable"),
+        &BytesN::from_array(env, &[0; 32]),
+    );
 }
 
 #[test]
-fn test_multiple_milestones() {
+fn test_create_grant_and_approve_milestone() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+
+    let grant_id = Symbol::new(&env, "grant_basic");
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000_000);
+
+    let grant = client.get_grant(&grant_id);
+    assert_eq!(grant.admin, admin);
+    assert_eq!(grant.grantee, grantee);
+    assert_eq!(grant.total_amount, 1_000_000);
+    assert_eq!(grant.released_amount, 0);
+
+    let milestone_id = Symbol::new(&env, "m1");
+    client.add_milestone(
+        &grant_id,
+        &milestone_id,
+        &300_000,
+        &String::from_str(&env, "Design"),
+    );
+    submit(&client, &env, &grant_id, &milestone_id);
+    client.approve_milestone(&grant_id, &milestone_id);
+
+    let milestone = client.get_milestone(&grant_id, &milestone_id);
+    assert_eq!(milestone.status, MilestoneStatus::Paid);
+    assert_eq!(client.get_grant(&grant_id).released_amount, 300_000);
+}
+
+#[test]
+fn test_grant_funds_are_escrowed_and_paid_out() {
     let env = Env::default();
-    let admin = Address::generate(&env);
-    let grantee = Address::generate(&env);
+    let (client, admin, grantee, token) = setup(&env);
+    let token_client = token::Client::new(&env, &token);
 
-    let contract_id = env.register(GrantContract, ());
-    let client = GrantContractClient::new(&env, &contract_id);
+    let grant_id = Symbol::new(&env, "grant_escrow");
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000_000);
+    assert_eq!(token_client.balance(&admin), ADMIN_BALANCE - 1_000_000);
+    assert_eq!(token_client.balance(&client.address), 1_000_000);
+
+    let milestone_id = Symbol::new(&env, "m1");
+    client.add_milestone(
+        &grant_id,
+        &milestone_id,
+        &400_000,
+        &String::from_str(&env, "Phase 1"),
+    );
+    assert_eq!(token_client.balance(&grantee), 0);
+
+    submit(&client, &env, &grant_id, &milestone_id);
+    client.approve_milestone(&grant_id, &milestone_id);
+    assert_eq!(token_client.balance(&grantee), 400_000);
+    assert_eq!(token_client.balance(&client.address), 600_000);
+}
+
+#[test]
+fn test_create_grant_requires_admin_balance() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+
+    let grant_id = Symbol::new(&env, "grant_broke");
+    assert_eq!(
+        client.try_create_grant(&grant_id, &admin, &grantee, &token, &(ADMIN_BALANCE + 1)),
+        Err(Ok(Error::InsufficientBalance))
+    );
+    assert_eq!(
+        client.try_get_grant(&grant_id),
+        Err(Ok(Error::GrantNotFound))
+    );
+}
+
+#[test]
+fn test_create_grant_rejects_duplicates_and_bad_amounts() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+
+    let grant_id = Symbol::new(&env, "grant_dup");
+    assert_eq!(
+        client.try_create_grant(&grant_id, &admin, &grantee, &token, &0),
+        Err(Ok(Error::InvalidAmount))
+    );
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000);
+    assert_eq!(
+        client.try_create_grant(&grant_id, &admin, &grantee, &token, &1_000),
+        Err(Ok(Error::AlreadyExists))
+    );
+    assert_eq!(
+        client.try_get_grant(&Symbol::new(&env, "missing")),
+        Err(Ok(Error::GrantNotFound))
+    );
+    assert_eq!(
+        client.try_approve_milestone(&grant_id, &Symbol::new(&env, "nope")),
+        Err(Ok(Error::MilestoneNotFound))
+    );
+}
+
+#[test]
+fn test_multiple_milestones() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
 
     // Create a grant
     let grant_id = Symbol::new(&env, "grant_multi");
-    client.create_grant(&grant_id, &admin, &grantee, &1_000_000).unwrap();
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000_000);
 
     // Add multiple milestones
     let milestone_1 = Symbol::new(&env, "m1");
     let milestone_2 = Symbol::new(&env, "m2");
     let milestone_3 = Symbol::new(&env, "m3");
 
-    client.add_milestone(&grant_id, &milestone_1, &250_000, &String::from_str(&env, "Phase 1")).unwrap();
-    client.add_milestone(&grant_id, &milestone_2, &350_000, &String::from_str(&env, "Phase 2")).unwrap();
-    client.add_milestone(&grant_id, &milestone_3, &400_000, &String::from_str(&env, "Phase 3")).unwrap();
+    client.add_milestone(
+        &grant_id,
+        &milestone_1,
+        &250_000,
+        &String::from_str(&env, "Phase 1"),
+    );
+    client.add_milestone(
+        &grant_id,
+        &milestone_2,
+        &350_000,
+        &String::from_str(&env, "Phase 2"),
+    );
+    client.add_milestone(
+        &grant_id,
+        &milestone_3,
+        &400_000,
+        &String::from_str(&env, "Phase 3"),
+    );
 
     // Approve first milestone
-    client.approve_milestone(&grant_id, &milestone_1).unwrap();
-    let grant_info = client.get_grant(&grant_id).unwrap();
-    assert_eq!(grant_info.3, 250_000);
+    submit(&client, &env, &grant_id, &milestone_1);
+    client.approve_milestone(&grant_id, &milestone_1);
+    let grant_info = client.get_grant(&grant_id);
+    assert_eq!(grant_info.released_amount, 250_000);
 
     // Approve second milestone
-    client.approve_milestone(&grant_id, &milestone_2).unwrap();
-    let grant_info = client.get_grant(&grant_id).unwrap();
-    assert_eq!(grant_info.3, 600_000);
+    submit(&client, &env, &grant_id, &milestone_2);
+    client.approve_milestone(&grant_id, &milestone_2);
+    let grant_info = client.get_grant(&grant_id);
+    assert_eq!(grant_info.released_amount, 600_000);
 
     // Approve third milestone
-    client.approve_milestone(&grant_id, &milestone_3).unwrap();
-    let grant_info = client.get_grant(&grant_id).unwrap();
-    assert_eq!(grant_info.3, 1_000_000);
+    submit(&client, &env, &grant_id, &milestone_3);
+    client.approve_milestone(&grant_id, &milestone_3);
+    let grant_info = client.get_grant(&grant_id);
+    assert_eq!(grant_info.released_amount, 1_000_000);
 }
 
 #[test]
 fn test_double_release_prevention() {
     let env = Env::default();
-    let admin = Address::generate(&env);
-    let grantee = Address::generate(&env);
-
-    let contract_id = env.register(GrantContract, ());
-    let client = GrantContractClient::new(&env, &contract_id);
+    let (client, admin, grantee, token) = setup(&env);
 
     // Create a grant and milestone
     let grant_id = Symbol::new(&env, "grant_double");
-    client.create_grant(&grant_id, &admin, &grantee, &1_000_000).unwrap();
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000_000);
 
     let milestone_id = Symbol::new(&env, "milestone_double");
     client.add_milestone(
@@ -60,68 +199,88 @@ fn test_double_release_prevention() {
         &milestone_id,
         &500_000,
         &String::from_str(&env, "Test"),
-    ).unwrap();
+    );
 
     // Approve once
-    client.approve_milestone(&grant_id, &milestone_id).unwrap();
+    submit(&client, &env, &grant_id, &milestone_id);
+    client.approve_milestone(&grant_id, &milestone_id);
 
     // Try to approve again - should fail
-    let result = client.approve_milestone(&grant_id, &milestone_id);
-    assert!(result.is_err());
+    let result = client.try_approve_milestone(&grant_id, &milestone_id);
+    assert_eq!(result, Err(Ok(Error::AlreadyReleased)));
 }
 
 #[test]
 fn test_get_remaining_amount() {
     let env = Env::default();
-    let admin = Address::generate(&env);
-    let grantee = Address::generate(&env);
-
-    let contract_id = env.register(GrantContract, ());
-    let client = GrantContractClient::new(&env, &contract_id);
+    let (client, admin, grantee, token) = setup(&env);
 
     // Create a grant
     let grant_id = Symbol::new(&env, "grant_remaining");
-    client.create_grant(&grant_id, &admin, &grantee, &1_000_000).unwrap();
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000_000);
 
     // Check remaining amount before any releases
-    let remaining = client.get_remaining_amount(&grant_id).unwrap();
+    let remaining = client.get_remaining_amount(&grant_id);
     assert_eq!(remaining, 1_000_000);
 
     // Add and approve a milestone
     let milestone_id = Symbol::new(&env, "m1");
-    client.add_milestone(&grant_id, &milestone_id, &400_000, &String::from_str(&env, "Phase 1")).unwrap();
-    client.approve_milestone(&grant_id, &milestone_id).unwrap();
+    client.add_milestone(
+        &grant_id,
+        &milestone_id,
+        &400_000,
+        &String::from_str(&env, "Phase 1"),
+    );
+    submit(&client, &env, &grant_id, &milestone_id);
+    client.approve_milestone(&grant_id, &milestone_id);
 
     // Check remaining amount after release
-    let remaining = client.get_remaining_amount(&grant_id).unwrap();
+    let remaining = client.get_remaining_amount(&grant_id);
     assert_eq!(remaining, 600_000);
 }
 
 #[test]
 fn test_exceed_total_grant_amount() {
     let env = Env::default();
-    let admin = Address::generate(&env);
-    let grantee = Address::generate(&env);
-
-    let contract_id = env.register(GrantContract, ());
-    let client = GrantContractClient::new(&env, &contract_id);
+    let (client, admin, grantee, token) = setup(&env);
 
     // Create a grant with 1M total
     let grant_id = Symbol::new(&env, "grant_exceed");
-    client.create_grant(&grant_id, &admin, &grantee, &1_000_000).unwrap();
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000_000);
 
     // Add milestone for 600K
     let milestone_1 = Symbol::new(&env, "m1");
-    client.add_milestone(&grant_id, &milestone_1, &600_000, &String::from_str(&env, "Phase 1")).unwrap();
-    client.approve_milestone(&grant_id, &milestone_1).unwrap();
+    client.add_milestone(
+        &grant_id,
+        &milestone_1,
+        &600_000,
+        &String::from_str(&env, "Phase 1"),
+    );
+    assert_eq!(client.get_unallocated_amount(&grant_id), 400_000);
 
-    // Add milestone for 500K (would exceed total)
+    // Adding a milestone for 500K would exceed the total and is refused up front
     let milestone_2 = Symbol::new(&env, "m2");
-    client.add_milestone(&grant_id, &milestone_2, &500_000, &String::from_str(&env, "Phase 2")).unwrap();
+    let result = client.try_add_milestone(
+        &grant_id,
+        &milestone_2,
+        &500_000,
+        &String::from_str(&env, "Phase 2"),
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidAmount)));
+    assert_eq!(
+        client.try_get_milestone(&grant_id, &milestone_2),
+        Err(Ok(Error::MilestoneNotFound))
+    );
+    assert_eq!(client.get_grant(&grant_id).allocated_amount, 600_000);
 
-    // Trying to approve should fail
-    let result = client.approve_milestone(&grant_id, &milestone_2);
-    assert!(result.is_err());
+    // The remaining 400K can still be allocated exactly
+    client.add_milestone(
+        &grant_id,
+        &milestone_2,
+        &400_000,
+        &String::from_str(&env, "Phase 2"),
+    );
+    assert_eq!(client.get_unallocated_amount(&grant_id), 0);
 }
 
 #[test]
@@ -138,16 +297,14 @@ fn test_grant_simulation_10_years() {
     // --------------------------------------------------
     // ✔ Start: nothing should be claimable
     // --------------------------------------------------
-    let claim0 =
-        grant::compute_claimable_balance(total, start, start, duration);
+    let claim0 = grant::compute_claimable_balance(total, start, start, duration);
     assert_eq!(claim0, 0);
 
     // --------------------------------------------------
     // ✔ Year 5: exactly 50%
     // --------------------------------------------------
     let year5 = start + duration / 2;
-    let claim5 =
-        grant::compute_claimable_balance(total, start, year5, duration);
+    let claim5 = grant::compute_claimable_balance(total, start, year5, duration);
 
     assert_eq!(claim5, total / 2);
 
@@ -155,8 +312,7 @@ fn test_grant_simulation_10_years() {
     // ✔ Year 10: 100% vested
     // --------------------------------------------------
     let year10 = start + duration;
-    let claim10 =
-        grant::compute_claimable_balance(total, start, year10, duration);
+    let claim10 = grant::compute_claimable_balance(total, start, year10, duration);
 
     assert_eq!(claim10, total);
 
@@ -164,8 +320,7 @@ fn test_grant_simulation_10_years() {
     // ✔ After expiry: must remain capped at total
     // --------------------------------------------------
     let after = year10 + 1_000_000;
-    let claim_after =
-        grant::compute_claimable_balance(total, start, after, duration);
+    let claim_after = grant::compute_claimable_balance(total, start, after, duration);
 
     assert_eq!(claim_after, total);
 
@@ -173,4 +328,2417 @@ fn test_grant_simulation_10_years() {
     // ✔ Verify constant equals 10-year duration
     // --------------------------------------------------
     assert_eq!(duration, 315_360_000u64);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_error_codes_match_errors_md() {
+    assert_eq!(Error::NotAuthorized as u32, 1);
+    assert_eq!(Error::InsufficientBalance as u32, 2);
+    assert_eq!(Error::GrantNotFound as u32, 3);
+    assert_eq!(Error::GrantPaused as u32, 4);
+    assert_eq!(Error::InvalidAmount as u32, 5);
+    assert_eq!(Error::AlreadyExists as u32, 6);
+    assert_eq!(Error::UnderDispute as u32, 7);
+    assert_eq!(Error::MilestoneNotFound as u32, 8);
+    assert_eq!(Error::AlreadyReleased as u32, 9);
+    assert_eq!(Error::InvalidGrantType as u32, 10);
+    assert_eq!(Error::InvalidSchedule as u32, 11);
+    assert_eq!(Error::GrantNotPaused as u32, 12);
+    assert_eq!(Error::ArbiterNotSet as u32, 13);
+    assert_eq!(Error::DisputeNotFound as u32, 14);
+    assert_eq!(Error::EvidenceLimitReached as u32, 15);
+    assert_eq!(Error::AlreadyVoted as u32, 16);
+    assert_eq!(Error::InvalidCouncil as u32, 17);
+    assert_eq!(Error::InvalidMilestoneState as u32, 18);
+    assert_eq!(Error::MilestoneExpired as u32, 19);
+    assert_eq!(Error::GrantCancelled as u32, 20);
+    assert_eq!(Error::NoPendingAdmin as u32, 21);
+    assert_eq!(Error::TimelockNotElapsed as u32, 22);
+    assert_eq!(Error::NoPendingGranteeChange as u32, 23);
+    assert_eq!(Error::BatchTooLarge as u32, 24);
+    assert_eq!(Error::InvalidArbiter as u32, 27);
+}
+
+#[test]
+fn test_vesting_grant_claims_only_the_delta() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let token_client = token::Client::new(&env, &token);
+    env.ledger().set_timestamp(1_700_000_000);
+
+    let grant_id = Symbol::new(&env, "stream");
+    client.initialize_grant(&grant_id, &admin, &grantee, &token, &1_000, &100, &0);
+
+    let info = client.get_grant_info(&grant_id);
+    assert_eq!(info.start_time, 1_700_000_000);
+    assert_eq!(info.end_time, 1_700_000_100);
+    assert_eq!(client.claimable_balance(&grant_id), 0);
+    assert_eq!(client.claim(&grant_id), 0);
+
+    env.ledger().set_timestamp(1_700_000_025);
+    assert_eq!(client.claimable_balance(&grant_id), 250);
+    assert_eq!(client.claim(&grant_id), 250);
+    assert_eq!(client.claimable_balance(&grant_id), 0);
+
+    env.ledger().set_timestamp(1_700_000_060);
+    assert_eq!(client.claim(&grant_id), 350);
+
+    env.ledger().set_timestamp(1_700_001_000);
+    assert_eq!(client.claim(&grant_id), 400);
+    assert_eq!(client.claim(&grant_id), 0);
+
+    assert_eq!(token_client.balance(&grantee), 1_000);
+    assert_eq!(client.get_grant_info(&grant_id).claimed_amount, 1_000);
+    assert_eq!(client.get_remaining_amount(&grant_id), 0);
+}
+
+#[test]
+fn test_vesting_and_milestone_grants_are_distinct() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+
+    let stream = Symbol::new(&env, "stream");
+    client.initialize_grant(&stream, &admin, &grantee, &token, &1_000, &100, &0);
+    assert_eq!(
+        client.try_add_milestone(
+            &stream,
+            &Symbol::new(&env, "m1"),
+            &10,
+            &String::from_str(&env, "x")
+        ),
+        Err(Ok(Error::InvalidGrantType))
+    );
+
+    let milestones = Symbol::new(&env, "milestones");
+    client.create_grant(&milestones, &admin, &grantee, &token, &1_000);
+    assert_eq!(
+        client.try_claim(&milestones),
+        Err(Ok(Error::InvalidGrantType))
+    );
+    assert_eq!(
+        client.try_claimable_balance(&milestones),
+        Err(Ok(Error::InvalidGrantType))
+    );
+}
+
+#[test]
+fn test_long_duration_simulation_10_years() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let duration: u64 = 315_360_000;
+    let total: i128 = 100_000_000;
+    let start: u64 = 1_700_000_000;
+    env.ledger().set_timestamp(start);
+
+    let grant_id = Symbol::new(&env, "ten_years");
+    client.initialize_grant(&grant_id, &admin, &grantee, &token, &total, &duration, &0);
+
+    env.ledger().set_timestamp(start + 157_680_000);
+    assert_eq!(client.claimable_balance(&grant_id), total / 2);
+    assert_eq!(client.claim(&grant_id), total / 2);
+
+    env.ledger().set_timestamp(start + duration);
+    assert_eq!(client.claim(&grant_id), total / 2);
+
+    env.ledger().set_timestamp(start + duration + 1_000_000);
+    assert_eq!(client.claimable_balance(&grant_id), 0);
+    assert_eq!(token::Client::new(&env, &token).balance(&grantee), total);
+}
+
+#[test]
+fn test_compute_claimable_balance_is_exact_for_huge_totals() {
+    // 2^128 - 1 is divisible by 3, so two thirds of it is exact.
+    let total = u128::MAX;
+    assert_eq!(
+        grant::compute_claimable_balance(total, 0, 2, 3),
+        total / 3 * 2
+    );
+
+    // One second short of the end must still hold back one second's worth
+    // (2^128 - 1 is also divisible by 2^64 - 1).
+    let duration = u64::MAX;
+    let claim = grant::compute_claimable_balance(total, 0, duration - 1, duration);
+    assert_eq!(claim, total - total / duration as u128);
+
+    // The 10-year schedule with a total near the i128 ceiling.
+    let total = i128::MAX as u128;
+    let duration: u64 = 315_360_000;
+    assert_eq!(
+        grant::compute_claimable_balance(
+            total,
+            1_700_000_000,
+            1_700_000_000 + duration / 2,
+            duration
+        ),
+        total / 2
+    );
+}
+
+#[test]
+fn test_mul_div_matches_narrow_arithmetic() {
+    let samples: [u128; 6] = [
+        0,
+        1,
+        7,
+        1_000_003,
+        u64::MAX as u128,
+        (u64::MAX as u128) << 1,
+    ];
+    for &a in samples.iter() {
+        for &b in samples.iter() {
+            for &d in samples.iter().filter(|d| **d != 0) {
+                if b > d {
+                    continue;
+                }
+                let expected = a.checked_mul(b).map(|p| p / d);
+                if let Some(expected) = expected {
+                    assert_eq!(grant::mul_div(a, b, d), expected);
+                }
+            }
+        }
+    }
+    assert_eq!(grant::mul_div(u128::MAX, u128::MAX, u128::MAX), u128::MAX);
+    assert_eq!(grant::mul_div(u128::MAX, 1 << 127, u128::MAX), 1 << 127);
+}
+
+#[test]
+fn test_compute_claimable_balance_is_monotonic() {
+    let total = u128::MAX - 12_345;
+    let start = 1_000;
+    let duration = 997;
+    let mut previous = 0;
+    for now in 0..(start + duration + 5) {
+        let claim = grant::compute_claimable_balance(total, start, now, duration);
+        assert!(claim >= previous);
+        assert!(claim <= total);
+        previous = claim;
+    }
+    assert_eq!(previous, total);
+}
+
+#[test]
+fn test_compute_claimable_balance_with_cliff() {
+    let total: u128 = 1_200;
+    let start: u64 = 1_000;
+    let duration: u64 = 1_200;
+    let cliff: u64 = 300;
+
+    assert_eq!(
+        grant::compute_claimable_balance_with_cliff(total, start, start, duration, cliff),
+        0
+    );
+    assert_eq!(
+        grant::compute_claimable_balance_with_cliff(
+            total,
+            start,
+            start + cliff - 1,
+            duration,
+            cliff
+        ),
+        0
+    );
+    // The accrued amount unlocks at once at the cliff.
+    assert_eq!(
+        grant::compute_claimable_balance_with_cliff(total, start, start + cliff, duration, cliff),
+        300
+    );
+    assert_eq!(
+        grant::compute_claimable_balance_with_cliff(total, start, start + 600, duration, cliff),
+        600
+    );
+    assert_eq!(
+        grant::compute_claimable_balance_with_cliff(
+            total,
+            start,
+            start + duration,
+            duration,
+            cliff
+        ),
+        total
+    );
+
+    // A zero cliff matches the plain linear schedule.
+    for now in [0, start, start + 1, start + 777, start + duration + 1] {
+        assert_eq!(
+            grant::compute_claimable_balance_with_cliff(total, start, now, duration, 0),
+            grant::compute_claimable_balance(total, start, now, duration)
+        );
+    }
+
+    // A cliff that would overflow the timestamp never unlocks.
+    assert_eq!(
+        grant::compute_claimable_balance_with_cliff(total, u64::MAX - 1, u64::MAX, 10, 5),
+        0
+    );
+}
+
+#[test]
+fn test_vesting_grant_with_cliff() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    env.ledger().set_timestamp(10_000);
+
+    let grant_id = Symbol::new(&env, "cliffed");
+    assert_eq!(
+        client.try_initialize_grant(&grant_id, &admin, &grantee, &token, &1_000, &100, &101),
+        Err(Ok(Error::InvalidSchedule))
+    );
+    client.initialize_grant(&grant_id, &admin, &grantee, &token, &1_000, &100, &25);
+    assert_eq!(
+        client.get_grant_info(&grant_id).schedule,
+        VestingSchedule::Linear(LinearSchedule {
+            start_time: 10_000,
+            cliff_time: 10_025,
+            end_time: 10_100,
+        })
+    );
+
+    env.ledger().set_timestamp(10_024);
+    assert_eq!(client.claimable_balance(&grant_id), 0);
+    assert_eq!(client.claim(&grant_id), 0);
+
+    env.ledger().set_timestamp(10_025);
+    assert_eq!(client.claim(&grant_id), 250);
+
+    env.ledger().set_timestamp(10_050);
+    assert_eq!(client.claim(&grant_id), 250);
+}
+
+fn breakpoints(env: &Env, points: &[(u64, i128)]) -> Vec<Breakpoint> {
+    let mut out = Vec::new(env);
+    for &(time, amount) in points {
+        out.push_back(Breakpoint { time, amount });
+    }
+    out
+}
+
+#[test]
+fn test_piecewise_schedule_evaluation() {
+    let env = Env::default();
+    // 25% cliff at t=100, linear to 50% at t=200, flat until t=300, rest by t=400.
+    let schedule = VestingSchedule::Piecewise(breakpoints(
+        &env,
+        &[(100, 250), (200, 500), (300, 500), (400, 1_000)],
+    ));
+    assert!(schedule.is_valid(1_000));
+    assert_eq!(schedule.start_time(), 100);
+    assert_eq!(schedule.end_time(), 400);
+
+    assert_eq!(schedule.vested_amount(1_000, 0), 0);
+    assert_eq!(schedule.vested_amount(1_000, 99), 0);
+    assert_eq!(schedule.vested_amount(1_000, 100), 250);
+    assert_eq!(schedule.vested_amount(1_000, 150), 375);
+    assert_eq!(schedule.vested_amount(1_000, 250), 500);
+    assert_eq!(schedule.vested_amount(1_000, 350), 750);
+    assert_eq!(schedule.vested_amount(1_000, 400), 1_000);
+    assert_eq!(schedule.vested_amount(1_000, u64::MAX), 1_000);
+
+    let mut previous = 0;
+    for now in 0..500 {
+        let vested = schedule.vested_amount(1_000, now);
+        assert!(vested >= previous);
+        previous = vested;
+    }
+}
+
+#[test]
+fn test_piecewise_schedule_validation() {
+    let env = Env::default();
+    let invalid = [
+        // Does not sum to the total.
+        breakpoints(&env, &[(100, 250), (200, 900)]),
+        // Times must strictly increase.
+        breakpoints(&env, &[(100, 250), (100, 1_000)]),
+        // Cumulative amounts must not decrease.
+        breakpoints(&env, &[(100, 600), (200, 500), (300, 1_000)]),
+        // Negative amounts are rejected.
+        breakpoints(&env, &[(100, -1), (200, 1_000)]),
+        // An empty curve never reaches the total.
+        Vec::new(&env),
+    ];
+    for points in invalid {
+        assert!(!VestingSchedule::Piecewise(points).is_valid(1_000));
+    }
+    assert!(VestingSchedule::Piecewise(breakpoints(&env, &[(5, 1_000)])).is_valid(1_000));
+}
+
+#[test]
+fn test_step_schedule_evaluation() {
+    let schedule = VestingSchedule::Step(StepSchedule {
+        start_time: 1_000,
+        period: grant::QUARTER,
+        periods: 4,
+    });
+    assert!(schedule.is_valid(1_001));
+    assert_eq!(schedule.end_time(), 1_000 + 4 * grant::QUARTER);
+
+    assert_eq!(schedule.vested_amount(1_001, 1_000), 0);
+    assert_eq!(schedule.vested_amount(1_001, 1_000 + grant::QUARTER - 1), 0);
+    assert_eq!(schedule.vested_amount(1_001, 1_000 + grant::QUARTER), 250);
+    assert_eq!(
+        schedule.vested_amount(1_001, 1_000 + 3 * grant::QUARTER),
+        750
+    );
+    assert_eq!(
+        schedule.vested_amount(1_001, 1_000 + 4 * grant::QUARTER),
+        1_001
+    );
+    assert_eq!(schedule.vested_amount(1_001, u64::MAX), 1_001);
+    // The cumulative amount is rounded down, spreading the remainder over
+    // the steps: 11 over three periods unlocks 3, 4 and 4.
+    assert_eq!(grant::compute_step_balance(11, 0, 1, 1, 3), 3);
+    assert_eq!(grant::compute_step_balance(11, 0, 2, 1, 3), 7);
+    assert_eq!(grant::compute_step_balance(11, 0, 3, 1, 3), 11);
+
+    assert!(!VestingSchedule::Step(StepSchedule {
+        start_time: 0,
+        period: 0,
+        periods: 4,
+    })
+    .is_valid(1_000));
+    assert!(!VestingSchedule::Step(StepSchedule {
+        start_time: u64::MAX - 10,
+        period: grant::MONTH,
+        periods: 1,
+    })
+    .is_valid(1_000));
+}
+
+#[test]
+fn test_scheduled_grant_claims_follow_the_curve() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let token_client = token::Client::new(&env, &token);
+
+    let grant_id = Symbol::new(&env, "piecewise");
+    let bad = VestingSchedule::Piecewise(breakpoints(&env, &[(100, 400), (200, 900)]));
+    assert_eq!(
+        client.try_initialize_scheduled_grant(&grant_id, &admin, &grantee, &token, &1_000, &bad),
+        Err(Ok(Error::InvalidSchedule))
+    );
+    assert_eq!(token_client.balance(&admin), ADMIN_BALANCE);
+
+    let schedule = VestingSchedule::Piecewise(breakpoints(&env, &[(100, 400), (200, 1_000)]));
+    client.initialize_scheduled_grant(&grant_id, &admin, &grantee, &token, &1_000, &schedule);
+    let info = client.get_grant_info(&grant_id);
+    assert_eq!((info.start_time, info.end_time), (100, 200));
+    assert_eq!(info.schedule, schedule);
+
+    env.ledger().set_timestamp(100);
+    assert_eq!(client.claim(&grant_id), 400);
+    env.ledger().set_timestamp(150);
+    assert_eq!(client.claim(&grant_id), 300);
+    env.ledger().set_timestamp(500);
+    assert_eq!(client.claim(&grant_id), 300);
+    assert_eq!(token_client.balance(&grantee), 1_000);
+
+    let stepped = Symbol::new(&env, "stepped");
+    let schedule = VestingSchedule::Step(StepSchedule {
+        start_time: 500,
+        period: grant::MONTH,
+        periods: 12,
+    });
+    client.initialize_scheduled_grant(&stepped, &admin, &grantee, &token, &1_200, &schedule);
+    env.ledger().set_timestamp(500 + 2 * grant::MONTH + 1);
+    assert_eq!(client.claimable_balance(&stepped), 200);
+    assert_eq!(client.get_grant_info(&stepped).schedule, schedule);
+}
+
+#[test]
+fn test_pause_blocks_approvals_and_claims() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+
+    let grant_id = Symbol::new(&env, "paused");
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000);
+    let milestone_id = Symbol::new(&env, "m1");
+    client.add_milestone(
+        &grant_id,
+        &milestone_id,
+        &500,
+        &String::from_str(&env, "Phase 1"),
+    );
+
+    assert_eq!(
+        client.try_pause_grant(&grant_id, &grantee),
+        Err(Ok(Error::NotAuthorized))
+    );
+    assert_eq!(
+        client.try_resume_grant(&grant_id, &admin, &false),
+        Err(Ok(Error::GrantNotPaused))
+    );
+
+    client.pause_grant(&grant_id, &admin);
+    assert_eq!(client.get_grant(&grant_id).paused_at, Some(0));
+    assert_eq!(
+        client.try_pause_grant(&grant_id, &admin),
+        Err(Ok(Error::GrantPaused))
+    );
+    submit(&client, &env, &grant_id, &milestone_id);
+    assert_eq!(
+        client.try_approve_milestone(&grant_id, &milestone_id),
+        Err(Ok(Error::GrantPaused))
+    );
+
+    client.resume_grant(&grant_id, &admin, &false);
+    assert_eq!(client.get_grant(&grant_id).paused_at, None);
+    client.approve_milestone(&grant_id, &milestone_id);
+    assert_eq!(client.get_remaining_amount(&grant_id), 500);
+}
+
+#[test]
+fn test_resume_with_extension_suspends_vesting_clock() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    env.ledger().set_timestamp(1_000);
+
+    let extended = Symbol::new(&env, "extended");
+    let plain = Symbol::new(&env, "plain");
+    client.initialize_grant(&extended, &admin, &grantee, &token, &1_000, &1_000, &0);
+    client.initialize_grant(&plain, &admin, &grantee, &token, &1_000, &1_000, &0);
+
+    env.ledger().set_timestamp(1_200);
+    client.pause_grant(&extended, &admin);
+    client.pause_grant(&plain, &admin);
+    assert_eq!(client.try_claim(&extended), Err(Ok(Error::GrantPaused)));
+
+    // Nothing accrues on either grant while paused.
+    env.ledger().set_timestamp(1_500);
+    assert_eq!(client.claimable_balance(&extended), 200);
+    assert_eq!(client.claimable_balance(&plain), 200);
+    client.resume_grant(&extended, &admin, &true);
+    client.resume_grant(&plain, &admin, &false);
+
+    // The extended grant picks up where it was paused; the other kept accruing.
+    assert_eq!(client.claimable_balance(&extended), 200);
+    assert_eq!(client.claimable_balance(&plain), 500);
+    assert_eq!(client.get_grant_info(&extended).end_time, 2_300);
+    assert_eq!(client.get_grant_info(&plain).end_time, 2_000);
+
+    env.ledger().set_timestamp(2_299);
+    assert!(client.claimable_balance(&extended) < 1_000);
+    env.ledger().set_timestamp(2_300);
+    assert_eq!(client.claim(&extended), 1_000);
+}
+
+#[test]
+fn test_delayed_schedule_preserves_vested_amounts() {
+    let env = Env::default();
+    let schedules = [
+        VestingSchedule::Linear(LinearSchedule {
+            start_time: 100,
+            cliff_time: 150,
+            end_time: 400,
+        }),
+        VestingSchedule::Piecewise(breakpoints(&env, &[(100, 250), (300, 1_000)])),
+        VestingSchedule::Step(StepSchedule {
+            start_time: 100,
+            period: 50,
+            periods: 6,
+        }),
+    ];
+    for schedule in schedules {
+        let delayed = schedule.delayed(1_000).unwrap();
+        assert!(delayed.is_valid(1_000));
+        assert_eq!(delayed.end_time(), schedule.end_time() + 1_000);
+        for now in (0..500).step_by(7) {
+            assert_eq!(
+                delayed.vested_amount(1_000, now + 1_000),
+                schedule.vested_amount(1_000, now)
+            );
+        }
+        assert!(schedule.delayed(u64::MAX).is_none());
+    }
+}
+
+/// Creates a 1,000 grant with three 300 milestones and an arbiter.
+fn setup_disputed_grant(env: &Env) -> (GrantContractClient<'_>, Address, Address, Address, Symbol) {
+    let (client, admin, grantee, token) = setup(env);
+    let grant_id = Symbol::new(env, "disputed");
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000);
+    for name in ["m1", "m2", "m3"] {
+        client.add_milestone(
+            &grant_id,
+            &Symbol::new(env, name),
+            &300,
+            &String::from_str(env, name),
+        );
+    }
+    let arbiter = Address::generate(env);
+    client.set_arbiter(&grant_id, &arbiter);
+    (client, admin, grantee, token, grant_id)
+}
+
+#[test]
+fn test_dispute_blocks_approval_until_resolved() {
+    let env = Env::default();
+    let (client, admin, grantee, token, grant_id) = setup_disputed_grant(&env);
+    let m1 = Symbol::new(&env, "m1");
+    let reason = String::from_str(&env, "Deliverable incomplete");
+
+    let outsider = Address::generate(&env);
+    assert_eq!(
+        client.try_open_dispute(&grant_id, &m1, &outsider, &reason),
+        Err(Ok(Error::NotAuthorized))
+    );
+    client.open_dispute(&grant_id, &m1, &admin, &reason);
+    assert_eq!(
+        client.try_open_dispute(&grant_id, &m1, &grantee, &reason),
+        Err(Ok(Error::AlreadyExists))
+    );
+    submit(&client, &env, &grant_id, &m1);
+    assert_eq!(
+        client.try_approve_milestone(&grant_id, &m1),
+        Err(Ok(Error::UnderDispute))
+    );
+
+    let evidence = BytesN::from_array(&env, &[7; 32]);
+    client.add_evidence(&grant_id, &m1, &grantee, &evidence);
+    let dispute = client.get_dispute(&grant_id, &m1);
+    assert_eq!(dispute.opened_by, admin);
+    assert_eq!(dispute.evidence.len(), 1);
+    assert_eq!(dispute.evidence.get(0), Some(evidence));
+    assert_eq!(dispute.status, DisputeStatus::Open);
+
+    client.resolve_dispute(&grant_id, &m1, &DisputeResolution::Approve);
+    assert_eq!(
+        client.get_dispute(&grant_id, &m1).status,
+        DisputeStatus::Resolved(DisputeResolution::Approve)
+    );
+    assert_eq!(
+        client.get_milestone(&grant_id, &m1).status,
+        MilestoneStatus::Paid
+    );
+    assert_eq!(token::Client::new(&env, &token).balance(&grantee), 300);
+    assert_eq!(
+        client.try_resolve_dispute(&grant_id, &m1, &DisputeResolution::Reject),
+        Err(Ok(Error::DisputeNotFound))
+    );
+}
+
+#[test]
+fn test_dispute_reject_and_partial_resolutions() {
+    let env = Env::default();
+    let (client, _admin, grantee, token, grant_id) = setup_disputed_grant(&env);
+    let m2 = Symbol::new(&env, "m2");
+    let m3 = Symbol::new(&env, "m3");
+    let reason = String::from_str(&env, "Scope dispute");
+
+    client.open_dispute(&grant_id, &m2, &grantee, &reason);
+    client.resolve_dispute(&grant_id, &m2, &DisputeResolution::Reject);
+    assert_eq!(
+        client.get_milestone(&grant_id, &m2).status,
+        MilestoneStatus::Rejected
+    );
+    assert_eq!(
+        client.try_approve_milestone(&grant_id, &m2),
+        Err(Ok(Error::UnderDispute))
+    );
+
+    client.open_dispute(&grant_id, &m3, &grantee, &reason);
+    assert_eq!(
+        client.try_resolve_dispute(&grant_id, &m3, &DisputeResolution::Partial(301)),
+        Err(Ok(Error::InvalidAmount))
+    );
+    client.resolve_dispute(&grant_id, &m3, &DisputeResolution::Partial(120));
+    let milestone = client.get_milestone(&grant_id, &m3);
+    assert_eq!(milestone.status, MilestoneStatus::Paid);
+    assert_eq!(milestone.amount, 120);
+    assert_eq!(client.get_remaining_amount(&grant_id), 880);
+    assert_eq!(token::Client::new(&env, &token).balance(&grantee), 120);
+}
+
+#[test]
+fn test_dispute_requires_arbiter_and_bounds_evidence() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let grant_id = Symbol::new(&env, "no_arbiter");
+    let m1 = Symbol::new(&env, "m1");
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000);
+    client.add_milestone(&grant_id, &m1, &100, &String::from_str(&env, "m1"));
+
+    let reason = String::from_str(&env, "reason");
+    assert_eq!(
+        client.try_open_dispute(&grant_id, &m1, &grantee, &reason),
+        Err(Ok(Error::ArbiterNotSet))
+    );
+    let evidence = BytesN::from_array(&env, &[1; 32]);
+    assert_eq!(
+        client.try_add_evidence(&grant_id, &m1, &grantee, &evidence),
+        Err(Ok(Error::DisputeNotFound))
+    );
+
+    client.set_arbiter(&grant_id, &Address::generate(&env));
+    client.open_dispute(&grant_id, &m1, &grantee, &reason);
+    for _ in 0..MAX_EVIDENCE {
+        client.add_evidence(&grant_id, &m1, &admin, &evidence);
+    }
+    assert_eq!(
+        client.try_add_evidence(&grant_id, &m1, &admin, &evidence),
+        Err(Ok(Error::EvidenceLimitReached))
+    );
+}
+
+#[test]
+fn test_arbiter_is_independent_and_fixed_during_disputes() {
+    let env = Env::default();
+    let (client, admin, grantee, _token, grant_id) = setup_disputed_grant(&env);
+    let m2 = Symbol::new(&env, "m2");
+    let replacement = Address::generate(&env);
+    assert_eq!(
+        client.try_set_arbiter(&grant_id, &admin),
+        Err(Ok(Error::InvalidArbiter))
+    );
+    assert_eq!(
+        client.try_set_arbiter(&grant_id, &grantee),
+        Err(Ok(Error::InvalidArbiter))
+    );
+
+    client.open_dispute(&grant_id, &m2, &grantee, &String::from_str(&env, "late"));
+    assert_eq!(
+        client.try_set_arbiter(&grant_id, &replacement),
+        Err(Ok(Error::UnderDispute))
+    );
+    client.resolve_dispute(&grant_id, &m2, &DisputeResolution::Reject);
+    client.set_arbiter(&grant_id, &replacement);
+    assert_eq!(
+        client.get_grant(&grant_id).arbiter,
+        Some(replacement.clone())
+    );
+
+    // Nor can the arbiter take the grant over as its grantee.
+    client.propose_grantee_change(&grant_id, &replacement);
+    assert_eq!(
+        client.try_confirm_grantee_change(&grant_id),
+        Err(Ok(Error::InvalidArbiter))
+    );
+}
+
+#[test]
+fn test_disputed_milestone_deadline_is_fixed() {
+    let env = Env::default();
+    let (client, _admin, grantee, _token, grant_id) = setup_disputed_grant(&env);
+    let m1 = Symbol::new(&env, "m1");
+    client.set_milestone_deadline(&grant_id, &m1, &Some(100));
+    client.open_dispute(&grant_id, &m1, &grantee, &String::from_str(&env, "late"));
+    assert_eq!(
+        client.try_set_milestone_deadline(&grant_id, &m1, &Some(1_000)),
+        Err(Ok(Error::UnderDispute))
+    );
+    assert_eq!(
+        client.try_set_milestone_deadline(&grant_id, &m1, &None),
+        Err(Ok(Error::UnderDispute))
+    );
+    assert_eq!(client.get_milestone(&grant_id, &m1).deadline, Some(100));
+}
+
+#[test]
+fn test_council_releases_milestone_at_threshold() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let grant_id = Symbol::new(&env, "council");
+    let m1 = Symbol::new(&env, "m1");
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000);
+    client.add_milestone(&grant_id, &m1, &400, &String::from_str(&env, "Phase 1"));
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let carol = Address::generate(&env);
+    let members = vec![&env, alice.clone(), bob.clone(), carol.clone()];
+    client.set_council(&grant_id, &members, &2);
+    assert_eq!(client.get_council(&grant_id).unwrap().threshold, 2);
+
+    // The admin alone can no longer approve.
+    submit(&client, &env, &grant_id, &m1);
+    assert_eq!(
+        client.try_approve_milestone(&grant_id, &m1),
+        Err(Ok(Error::NotAuthorized))
+    );
+    assert_eq!(
+        client.try_vote_milestone(&grant_id, &m1, &admin),
+        Err(Ok(Error::NotAuthorized))
+    );
+
+    assert!(!client.vote_milestone(&grant_id, &m1, &alice));
+    assert_eq!(
+        client.try_vote_milestone(&grant_id, &m1, &alice),
+        Err(Ok(Error::AlreadyVoted))
+    );
+    assert_eq!(client.get_votes(&grant_id, &m1), vec![&env, alice.clone()]);
+    assert_eq!(token::Client::new(&env, &token).balance(&grantee), 0);
+
+    assert!(client.vote_milestone(&grant_id, &m1, &carol));
+    assert_eq!(
+        client.get_milestone(&grant_id, &m1).status,
+        MilestoneStatus::Paid
+    );
+    assert_eq!(token::Client::new(&env, &token).balance(&grantee), 400);
+    assert_eq!(
+        client.try_vote_milestone(&grant_id, &m1, &bob),
+        Err(Ok(Error::AlreadyReleased))
+    );
+
+    // Council members may pause the grant as well.
+    client.pause_grant(&grant_id, &bob);
+    assert!(client.get_grant(&grant_id).paused_at.is_some());
+}
+
+#[test]
+fn test_council_configuration_is_validated() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let grant_id = Symbol::new(&env, "council_cfg");
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let invalid = [
+        (Vec::new(&env), 1),
+        (vec![&env, alice.clone(), bob.clone()], 0),
+        (vec![&env, alice.clone(), bob.clone()], 3),
+        (vec![&env, alice.clone(), alice.clone()], 1),
+    ];
+    for (members, threshold) in invalid {
+        assert_eq!(
+            client.try_set_council(&grant_id, &members, &threshold),
+            Err(Ok(Error::InvalidCouncil))
+        );
+    }
+
+    let mut too_many = Vec::new(&env);
+    for _ in 0..=MAX_COUNCIL_MEMBERS {
+        too_many.push_back(Address::generate(&env));
+    }
+    assert_eq!(
+        client.try_set_council(&grant_id, &too_many, &1),
+        Err(Ok(Error::InvalidCouncil))
+    );
+    assert_eq!(client.get_council(&grant_id), None);
+}
+
+#[test]
+fn test_removed_council_members_do_not_count() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let grant_id = Symbol::new(&env, "council_swap");
+    let m1 = Symbol::new(&env, "m1");
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000);
+    client.add_milestone(&grant_id, &m1, &400, &String::from_str(&env, "Phase 1"));
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let carol = Address::generate(&env);
+    client.set_council(&grant_id, &vec![&env, alice.clone(), bob.clone()], &2);
+    submit(&client, &env, &grant_id, &m1);
+    assert!(!client.vote_milestone(&grant_id, &m1, &alice));
+
+    client.set_council(&grant_id, &vec![&env, bob.clone(), carol.clone()], &2);
+    assert!(!client.vote_milestone(&grant_id, &m1, &bob));
+    assert!(client.vote_milestone(&grant_id, &m1, &carol));
+}
+
+#[test]
+fn test_milestone_review_state_machine() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let grant_id = Symbol::new(&env, "reviewed");
+    let m1 = Symbol::new(&env, "m1");
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000);
+    client.add_milestone(&grant_id, &m1, &400, &String::from_str(&env, "Phase 1"));
+    assert_eq!(
+        client.get_milestone(&grant_id, &m1).status,
+        MilestoneStatus::Pending
+    );
+
+    // Nothing to approve or review before the grantee submits.
+    assert_eq!(
+        client.try_approve_milestone(&grant_id, &m1),
+        Err(Ok(Error::InvalidMilestoneState))
+    );
+    assert_eq!(
+        client.try_start_review(&grant_id, &m1, &admin),
+        Err(Ok(Error::InvalidMilestoneState))
+    );
+
+    let uri = String::from_str(&env, "ipfs://draft");
+    let hash = BytesN::from_array(&env, &[1; 32]);
+    client.submit_milestone(&grant_id, &m1, &uri, &hash);
+    assert_eq!(
+        client.get_milestone(&grant_id, &m1).status,
+        MilestoneStatus::Submitted
+    );
+    assert_eq!(
+        client.try_submit_milestone(&grant_id, &m1, &uri, &hash),
+        Err(Ok(Error::InvalidMilestoneState))
+    );
+    assert_eq!(
+        client.try_start_review(&grant_id, &m1, &grantee),
+        Err(Ok(Error::NotAuthorized))
+    );
+
+    client.start_review(&grant_id, &m1, &admin);
+    assert_eq!(
+        client.get_milestone(&grant_id, &m1).status,
+        MilestoneStatus::UnderReview
+    );
+
+    let feedback = String::from_str(&env, "Add benchmarks");
+    client.request_changes(&grant_id, &m1, &admin, &feedback);
+    assert_eq!(
+        client.get_milestone(&grant_id, &m1).status,
+        MilestoneStatus::Pending
+    );
+    assert_eq!(client.get_submission(&grant_id, &m1).feedback, feedback);
+
+    let final_uri = String::from_str(&env, "ipfs://final");
+    let final_hash = BytesN::from_array(&env, &[2; 32]);
+    client.submit_milestone(&grant_id, &m1, &final_uri, &final_hash);
+    let submission = client.get_submission(&grant_id, &m1);
+    assert_eq!(submission.uri, final_uri);
+    assert_eq!(submission.hash, final_hash);
+    assert_eq!(submission.feedback, String::from_str(&env, ""));
+
+    client.start_review(&grant_id, &m1, &admin);
+    client.approve_milestone(&grant_id, &m1);
+    assert_eq!(
+        client.get_milestone(&grant_id, &m1).status,
+        MilestoneStatus::Paid
+    );
+    assert_eq!(
+        client.try_request_changes(&grant_id, &m1, &admin, &feedback),
+        Err(Ok(Error::InvalidMilestoneState))
+    );
+}
+
+#[test]
+fn test_request_changes_clears_council_votes() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let grant_id = Symbol::new(&env, "revote");
+    let m1 = Symbol::new(&env, "m1");
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000);
+    client.add_milestone(&grant_id, &m1, &400, &String::from_str(&env, "Phase 1"));
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    client.set_council(&grant_id, &vec![&env, alice.clone(), bob.clone()], &2);
+    assert_eq!(
+        client.try_vote_milestone(&grant_id, &m1, &alice),
+        Err(Ok(Error::InvalidMilestoneState))
+    );
+
+    submit(&client, &env, &grant_id, &m1);
+    assert!(!client.vote_milestone(&grant_id, &m1, &alice));
+    client.request_changes(&grant_id, &m1, &bob, &String::from_str(&env, "Fix tests"));
+    assert_eq!(client.get_votes(&grant_id, &m1).len(), 0);
+
+    submit(&client, &env, &grant_id, &m1);
+    assert!(!client.vote_milestone(&grant_id, &m1, &bob));
+    assert!(client.vote_milestone(&grant_id, &m1, &alice));
+}
+
+#[test]
+fn test_milestone_expires_after_deadline() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let token_client = token::Client::new(&env, &token);
+    let grant_id = Symbol::new(&env, "deadline");
+    let m1 = Symbol::new(&env, "m1");
+    let m2 = Symbol::new(&env, "m2");
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000);
+    client.add_milestone(&grant_id, &m1, &400, &String::from_str(&env, "Phase 1"));
+    client.add_milestone(&grant_id, &m2, &300, &String::from_str(&env, "Phase 2"));
+    client.set_milestone_deadline(&grant_id, &m1, &Some(100));
+    client.set_milestone_deadline(&grant_id, &m2, &Some(200));
+
+    // Submitting before the deadline keeps m2 open past it.
+    env.ledger().set_timestamp(50);
+    submit(&client, &env, &grant_id, &m2);
+
+    env.ledger().set_timestamp(100);
+    assert_eq!(
+        client.get_milestone(&grant_id, &m1).status,
+        MilestoneStatus::Pending
+    );
+
+    env.ledger().set_timestamp(101);
+    assert_eq!(
+        client.get_milestone(&grant_id, &m1).status,
+        MilestoneStatus::Expired
+    );
+    assert_eq!(
+        client.try_submit_milestone(
+            &grant_id,
+            &m1,
+            &String::from_str(&env, "ipfs://late"),
+            &BytesN::from_array(&env, &[0; 32])
+        ),
+        Err(Ok(Error::MilestoneExpired))
+    );
+    assert_eq!(
+        client.try_approve_milestone(&grant_id, &m1),
+        Err(Ok(Error::MilestoneExpired))
+    );
+    assert_eq!(
+        client.try_set_milestone_deadline(&grant_id, &m1, &Some(1_000)),
+        Err(Ok(Error::MilestoneExpired))
+    );
+    assert_eq!(token_client.balance(&client.address), 1_000);
+
+    env.ledger().set_timestamp(500);
+    client.approve_milestone(&grant_id, &m2);
+    assert_eq!(
+        client.get_milestone(&grant_id, &m2).status,
+        MilestoneStatus::Paid
+    );
+}
+
+#[test]
+fn test_reclaim_expired_milestone_refunds_admin() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let token_client = token::Client::new(&env, &token);
+    let grant_id = Symbol::new(&env, "reclaim");
+    let m1 = Symbol::new(&env, "m1");
+    let m2 = Symbol::new(&env, "m2");
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000);
+    client.add_milestone(&grant_id, &m1, &400, &String::from_str(&env, "Phase 1"));
+    client.add_milestone(&grant_id, &m2, &300, &String::from_str(&env, "Phase 2"));
+    client.set_milestone_deadline(&grant_id, &m1, &Some(100));
+    client.set_milestone_deadline(&grant_id, &m2, &Some(100));
+    assert_eq!(
+        client.try_reclaim_milestone(&grant_id, &m1),
+        Err(Ok(Error::InvalidMilestoneState))
+    );
+
+    // Removing a deadline keeps the milestone open indefinitely.
+    client.set_milestone_deadline(&grant_id, &m2, &None);
+
+    env.ledger().set_timestamp(101);
+    assert_eq!(client.reclaim_milestone(&grant_id, &m1), 400);
+    assert_eq!(
+        client.get_milestone(&grant_id, &m1).status,
+        MilestoneStatus::Reclaimed
+    );
+    assert_eq!(client.get_grant(&grant_id).total_amount, 600);
+    assert_eq!(client.get_remaining_amount(&grant_id), 600);
+    assert_eq!(token_client.balance(&admin), ADMIN_BALANCE - 600);
+    assert_eq!(token_client.balance(&client.address), 600);
+    assert_eq!(
+        client.try_reclaim_milestone(&grant_id, &m1),
+        Err(Ok(Error::InvalidMilestoneState))
+    );
+
+    assert_eq!(
+        client.get_milestone(&grant_id, &m2).status,
+        MilestoneStatus::Pending
+    );
+    submit(&client, &env, &grant_id, &m2);
+    client.approve_milestone(&grant_id, &m2);
+    assert_eq!(token_client.balance(&grantee), 300);
+}
+
+#[test]
+fn test_changes_requested_after_deadline_do_not_expire_milestone() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let token_client = token::Client::new(&env, &token);
+    let grant_id = Symbol::new(&env, "latefix");
+    let m1 = Symbol::new(&env, "m1");
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000);
+    client.add_milestone(&grant_id, &m1, &400, &String::from_str(&env, "Phase 1"));
+    client.set_milestone_deadline(&grant_id, &m1, &Some(100));
+
+    env.ledger().set_timestamp(50);
+    submit(&client, &env, &grant_id, &m1);
+    env.ledger().set_timestamp(200);
+    client.request_changes(&grant_id, &m1, &admin, &String::from_str(&env, "Fix tests"));
+
+    // Submitted on time, so the grantee keeps the chance to fix it.
+    assert_eq!(
+        client.get_milestone(&grant_id, &m1).status,
+        MilestoneStatus::Pending
+    );
+    assert_eq!(
+        client.try_reclaim_milestone(&grant_id, &m1),
+        Err(Ok(Error::InvalidMilestoneState))
+    );
+    submit(&client, &env, &grant_id, &m1);
+    client.approve_milestone(&grant_id, &m1);
+    assert_eq!(token_client.balance(&grantee), 400);
+}
+
+#[test]
+fn test_update_and_remove_milestone_rebalance_allocation() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let grant_id = Symbol::new(&env, "rebalance");
+    let m1 = Symbol::new(&env, "m1");
+    let m2 = Symbol::new(&env, "m2");
+    let m3 = Symbol::new(&env, "m3");
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000);
+    client.add_milestone(&grant_id, &m1, &400, &String::from_str(&env, "Phase 1"));
+    client.add_milestone(&grant_id, &m2, &600, &String::from_str(&env, "Phase 2"));
+
+    // Growing m1 needs room that m2 currently holds.
+    assert_eq!(
+        client.try_update_milestone(&grant_id, &m1, &500, &String::from_str(&env, "Bigger")),
+        Err(Ok(Error::InvalidAmount))
+    );
+    client.update_milestone(&grant_id, &m2, &300, &String::from_str(&env, "Smaller"));
+    client.update_milestone(&grant_id, &m1, &500, &String::from_str(&env, "Bigger"));
+    let milestone = client.get_milestone(&grant_id, &m1);
+    assert_eq!(milestone.amount, 500);
+    assert_eq!(milestone.description, String::from_str(&env, "Bigger"));
+    assert_eq!(client.get_unallocated_amount(&grant_id), 200);
+    assert_eq!(
+        client.try_update_milestone(&grant_id, &m1, &0, &String::from_str(&env, "Empty")),
+        Err(Ok(Error::InvalidAmount))
+    );
+
+    // Submitted and paid milestones are frozen.
+    submit(&client, &env, &grant_id, &m1);
+    assert_eq!(
+        client.try_update_milestone(&grant_id, &m1, &100, &String::from_str(&env, "Late")),
+        Err(Ok(Error::InvalidMilestoneState))
+    );
+    assert_eq!(
+        client.try_remove_milestone(&grant_id, &m1),
+        Err(Ok(Error::InvalidMilestoneState))
+    );
+    client.approve_milestone(&grant_id, &m1);
+    assert_eq!(client.get_unallocated_amount(&grant_id), 200);
+
+    client.remove_milestone(&grant_id, &m2);
+    assert_eq!(
+        client.try_get_milestone(&grant_id, &m2),
+        Err(Ok(Error::MilestoneNotFound))
+    );
+    assert_eq!(
+        client.get_grant(&grant_id).milestones,
+        vec![&env, m1.clone()]
+    );
+    assert_eq!(client.get_unallocated_amount(&grant_id), 500);
+
+    client.add_milestone(&grant_id, &m3, &500, &String::from_str(&env, "Phase 3"));
+    assert_eq!(client.get_unallocated_amount(&grant_id), 0);
+}
+
+#[test]
+fn test_released_milestone_amounts_return_to_pool() {
+    let env = Env::default();
+    let (client, admin, _grantee, _token, grant_id) = setup_disputed_grant(&env);
+    let m1 = Symbol::new(&env, "m1");
+    let m2 = Symbol::new(&env, "m2");
+    let m3 = Symbol::new(&env, "m3");
+    assert_eq!(client.get_unallocated_amount(&grant_id), 100);
+
+    // Rejection and partial payouts free the unpaid part of the milestone.
+    client.open_dispute(&grant_id, &m1, &admin, &String::from_str(&env, "Missing"));
+    client.resolve_dispute(&grant_id, &m1, &DisputeResolution::Reject);
+    assert_eq!(client.get_unallocated_amount(&grant_id), 400);
+    submit(&client, &env, &grant_id, &m2);
+    client.open_dispute(&grant_id, &m2, &admin, &String::from_str(&env, "Partial"));
+    client.resolve_dispute(&grant_id, &m2, &DisputeResolution::Partial(100));
+    assert_eq!(client.get_unallocated_amount(&grant_id), 600);
+
+    // Expired milestones return to the pool when removed.
+    client.set_milestone_deadline(&grant_id, &m3, &Some(10));
+    env.ledger().set_timestamp(11);
+    client.remove_milestone(&grant_id, &m3);
+    assert_eq!(client.get_unallocated_amount(&grant_id), 900);
+    assert_eq!(client.get_grant(&grant_id).allocated_amount, 100);
+}
+
+#[test]
+fn test_cancel_vesting_grant_settles_pro_rata() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let token_client = token::Client::new(&env, &token);
+    let grant_id = Symbol::new(&env, "cancelled");
+    client.initialize_grant(&grant_id, &admin, &grantee, &token, &1_000, &1_000, &0);
+
+    env.ledger().set_timestamp(300);
+    assert_eq!(client.claim(&grant_id), 300);
+
+    env.ledger().set_timestamp(600);
+    assert_eq!(client.cancel_grant(&grant_id), 300);
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [Cancelled {
+            grant_id: grant_id.clone(),
+            paid_amount: 300,
+            refunded_amount: 400,
+        }
+        .to_xdr(&env, &client.address)]
+    );
+    assert_eq!(token_client.balance(&grantee), 600);
+    assert_eq!(token_client.balance(&admin), ADMIN_BALANCE - 600);
+    assert_eq!(token_client.balance(&client.address), 0);
+
+    let grant = client.get_grant(&grant_id);
+    assert_eq!(grant.status, GrantStatus::Cancelled);
+    assert_eq!(grant.total_amount, 600);
+    assert_eq!(grant.released_amount, 600);
+    assert_eq!(client.get_remaining_amount(&grant_id), 0);
+
+    // Nothing accrues or moves after cancellation.
+    env.ledger().set_timestamp(2_000);
+    assert_eq!(client.claimable_balance(&grant_id), 0);
+    assert_eq!(client.try_claim(&grant_id), Err(Ok(Error::GrantCancelled)));
+    assert_eq!(
+        client.try_pause_grant(&grant_id, &admin),
+        Err(Ok(Error::GrantCancelled))
+    );
+    assert_eq!(
+        client.try_cancel_grant(&grant_id),
+        Err(Ok(Error::GrantCancelled))
+    );
+}
+
+#[test]
+fn test_cancel_paused_vesting_grant_settles_at_pause() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let token_client = token::Client::new(&env, &token);
+    let grant_id = Symbol::new(&env, "paused");
+    client.initialize_grant(&grant_id, &admin, &grantee, &token, &1_000, &1_000, &0);
+
+    env.ledger().set_timestamp(250);
+    client.pause_grant(&grant_id, &admin);
+    env.ledger().set_timestamp(900);
+    assert_eq!(client.cancel_grant(&grant_id), 250);
+    assert_eq!(token_client.balance(&grantee), 250);
+    assert_eq!(token_client.balance(&admin), ADMIN_BALANCE - 250);
+}
+
+#[test]
+fn test_cancel_milestone_grant_blocks_further_changes() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let token_client = token::Client::new(&env, &token);
+    let grant_id = Symbol::new(&env, "stopped");
+    let m1 = Symbol::new(&env, "m1");
+    let m2 = Symbol::new(&env, "m2");
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000);
+    client.add_milestone(&grant_id, &m1, &400, &String::from_str(&env, "Phase 1"));
+    client.add_milestone(&grant_id, &m2, &300, &String::from_str(&env, "Phase 2"));
+    submit(&client, &env, &grant_id, &m1);
+    client.approve_milestone(&grant_id, &m1);
+    submit(&client, &env, &grant_id, &m2);
+
+    // Approved milestones were already paid; the unapproved rest is refunded.
+    assert_eq!(client.cancel_grant(&grant_id), 0);
+    assert_eq!(token_client.balance(&grantee), 400);
+    assert_eq!(token_client.balance(&admin), ADMIN_BALANCE - 400);
+    assert_eq!(token_client.balance(&client.address), 0);
+    assert_eq!(client.get_unallocated_amount(&grant_id), 0);
+
+    assert_eq!(
+        client.try_approve_milestone(&grant_id, &m2),
+        Err(Ok(Error::GrantCancelled))
+    );
+    assert_eq!(
+        client.try_add_milestone(
+            &grant_id,
+            &Symbol::new(&env, "m3"),
+            &1,
+            &String::from_str(&env, "Late")
+        ),
+        Err(Ok(Error::GrantCancelled))
+    );
+    assert_eq!(
+        client.try_set_arbiter(&grant_id, &admin),
+        Err(Ok(Error::GrantCancelled))
+    );
+    assert_eq!(
+        client.get_milestone(&grant_id, &m2).status,
+        MilestoneStatus::Submitted
+    );
+}
+
+#[test]
+fn test_milestone_lifecycle_events() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let grant_id = Symbol::new(&env, "indexed");
+    let m1 = Symbol::new(&env, "m1");
+
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000);
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [
+            GrantCreated {
+                grant_id: grant_id.clone(),
+                admin: admin.clone(),
+                grantee: grantee.clone(),
+                token: token.clone(),
+                total_amount: 1_000,
+            }
+            .to_xdr(&env, &client.address),
+            Funded {
+                grant_id: grant_id.clone(),
+                from: admin.clone(),
+                amount: 1_000,
+            }
+            .to_xdr(&env, &client.address),
+        ]
+    );
+
+    client.add_milestone(&grant_id, &m1, &400, &String::from_str(&env, "Phase 1"));
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [MilestoneAdded {
+            grant_id: grant_id.clone(),
+            milestone_id: m1.clone(),
+            amount: 400,
+        }
+        .to_xdr(&env, &client.address)]
+    );
+
+    submit(&client, &env, &grant_id, &m1);
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [MilestoneSubmitted {
+            grant_id: grant_id.clone(),
+            milestone_id: m1.clone(),
+            uri: String::from_str(&env, "ipfs://deliverable"),
+            hash: BytesN::from_array(&env, &[0; 32]),
+        }
+        .to_xdr(&env, &client.address)]
+    );
+
+    client.approve_milestone(&grant_id, &m1);
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [
+            MilestoneApproved {
+                grant_id: grant_id.clone(),
+                milestone_id: m1.clone(),
+                amount: 400,
+            }
+            .to_xdr(&env, &client.address),
+            Released {
+                grant_id: grant_id.clone(),
+                milestone_id: m1.clone(),
+                to: grantee.clone(),
+                amount: 400,
+            }
+            .to_xdr(&env, &client.address),
+        ]
+    );
+}
+
+#[test]
+fn test_vesting_lifecycle_events() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let grant_id = Symbol::new(&env, "streamed");
+    client.initialize_grant(&grant_id, &admin, &grantee, &token, &1_000, &1_000, &0);
+
+    env.ledger().set_timestamp(200);
+    client.claim(&grant_id);
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [Claimed {
+            grant_id: grant_id.clone(),
+            to: grantee.clone(),
+            amount: 200,
+        }
+        .to_xdr(&env, &client.address)]
+    );
+
+    // Claiming with nothing new vested is silent.
+    client.claim(&grant_id);
+    assert_eq!(
+        env.events()
+            .all()
+            .filter_by_contract(&client.address)
+            .events()
+            .len(),
+        0
+    );
+
+    client.pause_grant(&grant_id, &admin);
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [Paused {
+            grant_id: grant_id.clone(),
+            by: admin.clone(),
+        }
+        .to_xdr(&env, &client.address)]
+    );
+
+    env.ledger().set_timestamp(250);
+    client.resume_grant(&grant_id, &admin, &true);
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [Resumed {
+            grant_id: grant_id.clone(),
+            by: admin.clone(),
+            extended_by: 50,
+        }
+        .to_xdr(&env, &client.address)]
+    );
+}
+
+#[test]
+fn test_dispute_events() {
+    let env = Env::default();
+    let (client, admin, grantee, _token, grant_id) = setup_disputed_grant(&env);
+    let m1 = Symbol::new(&env, "m1");
+    let reason = String::from_str(&env, "Incomplete");
+    submit(&client, &env, &grant_id, &m1);
+
+    client.open_dispute(&grant_id, &m1, &admin, &reason);
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [Disputed {
+            grant_id: grant_id.clone(),
+            milestone_id: m1.clone(),
+            opened_by: admin.clone(),
+            reason,
+        }
+        .to_xdr(&env, &client.address)]
+    );
+
+    client.resolve_dispute(&grant_id, &m1, &DisputeResolution::Partial(120));
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [
+            MilestoneApproved {
+                grant_id: grant_id.clone(),
+                milestone_id: m1.clone(),
+                amount: 120,
+            }
+            .to_xdr(&env, &client.address),
+            Released {
+                grant_id: grant_id.clone(),
+                milestone_id: m1.clone(),
+                to: grantee.clone(),
+                amount: 120,
+            }
+            .to_xdr(&env, &client.address),
+            DisputeResolved {
+                grant_id: grant_id.clone(),
+                milestone_id: m1.clone(),
+                resolution: DisputeResolution::Partial(120),
+            }
+            .to_xdr(&env, &client.address),
+        ]
+    );
+}
+
+#[test]
+fn test_review_events() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let grant_id = Symbol::new(&env, "reviewed");
+    let m1 = Symbol::new(&env, "m1");
+    let feedback = String::from_str(&env, "Fix tests");
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000);
+    client.add_milestone(&grant_id, &m1, &400, &String::from_str(&env, "Phase 1"));
+    submit(&client, &env, &grant_id, &m1);
+
+    client.start_review(&grant_id, &m1, &admin);
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [ReviewStarted {
+            grant_id: grant_id.clone(),
+            milestone_id: m1.clone(),
+            reviewer: admin.clone(),
+        }
+        .to_xdr(&env, &client.address)]
+    );
+
+    client.request_changes(&grant_id, &m1, &admin, &feedback);
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [ChangesRequested {
+            grant_id: grant_id.clone(),
+            milestone_id: m1.clone(),
+            reviewer: admin.clone(),
+            feedback,
+        }
+        .to_xdr(&env, &client.address)]
+    );
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    client.set_council(&grant_id, &vec![&env, alice.clone(), bob.clone()], &2);
+    submit(&client, &env, &grant_id, &m1);
+    client.vote_milestone(&grant_id, &m1, &alice);
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [Voted {
+            grant_id: grant_id.clone(),
+            milestone_id: m1.clone(),
+            member: alice.clone(),
+            tally: 1,
+        }
+        .to_xdr(&env, &client.address)]
+    );
+
+    client.vote_milestone(&grant_id, &m1, &bob);
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [
+            Voted {
+                grant_id: grant_id.clone(),
+                milestone_id: m1.clone(),
+                member: bob.clone(),
+                tally: 2,
+            }
+            .to_xdr(&env, &client.address),
+            MilestoneApproved {
+                grant_id: grant_id.clone(),
+                milestone_id: m1.clone(),
+                amount: 400,
+            }
+            .to_xdr(&env, &client.address),
+            Released {
+                grant_id: grant_id.clone(),
+                milestone_id: m1.clone(),
+                to: grantee.clone(),
+                amount: 400,
+            }
+            .to_xdr(&env, &client.address),
+        ]
+    );
+}
+
+#[test]
+fn test_milestone_management_events() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let grant_id = Symbol::new(&env, "managed");
+    let m1 = Symbol::new(&env, "m1");
+    let m2 = Symbol::new(&env, "m2");
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000);
+    client.add_milestone(&grant_id, &m1, &400, &String::from_str(&env, "Phase 1"));
+    client.add_milestone(&grant_id, &m2, &300, &String::from_str(&env, "Phase 2"));
+
+    client.update_milestone(&grant_id, &m1, &500, &String::from_str(&env, "Phase 1b"));
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [MilestoneUpdated {
+            grant_id: grant_id.clone(),
+            milestone_id: m1.clone(),
+            amount: 500,
+        }
+        .to_xdr(&env, &client.address)]
+    );
+
+    client.set_milestone_deadline(&grant_id, &m1, &Some(100));
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [DeadlineSet {
+            grant_id: grant_id.clone(),
+            milestone_id: m1.clone(),
+            deadline: Some(100),
+        }
+        .to_xdr(&env, &client.address)]
+    );
+
+    client.remove_milestone(&grant_id, &m2);
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [MilestoneRemoved {
+            grant_id: grant_id.clone(),
+            milestone_id: m2.clone(),
+            amount: 300,
+        }
+        .to_xdr(&env, &client.address)]
+    );
+
+    env.ledger().set_timestamp(101);
+    client.reclaim_milestone(&grant_id, &m1);
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [Reclaimed {
+            grant_id: grant_id.clone(),
+            milestone_id: m1.clone(),
+            to: admin.clone(),
+            amount: 500,
+        }
+        .to_xdr(&env, &client.address)]
+    );
+}
+
+/// TTL of the grant's persistent entry and of the contract instance.
+fn grant_ttls(env: &Env, client: &GrantContractClient, key: &DataKey) -> (u32, u32) {
+    env.as_contract(&client.address, || {
+        (
+            env.storage().persistent().get_ttl(key),
+            env.storage().instance().get_ttl(),
+        )
+    })
+}
+
+#[test]
+fn test_touching_a_grant_extends_storage_ttl() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let grant_id = Symbol::new(&env, "alive");
+    let m1 = Symbol::new(&env, "m1");
+    let grant_key = DataKey::Grant(grant_id.clone());
+    let milestone_key = DataKey::Milestone(grant_id.clone(), m1.clone());
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000);
+    client.add_milestone(&grant_id, &m1, &400, &String::from_str(&env, "Phase 1"));
+    assert_eq!(
+        grant_ttls(&env, &client, &grant_key),
+        (TTL_EXTEND_TO, TTL_EXTEND_TO)
+    );
+    assert_eq!(grant_ttls(&env, &client, &milestone_key).0, TTL_EXTEND_TO);
+
+    // Entries are only extended once their TTL drops below the threshold.
+    let start = env.ledger().sequence();
+    env.ledger()
+        .set_sequence_number(start + TTL_EXTEND_TO - TTL_THRESHOLD - 1);
+    client.get_grant(&grant_id);
+    assert_eq!(grant_ttls(&env, &client, &grant_key).0, TTL_THRESHOLD + 1);
+
+    env.ledger()
+        .set_sequence_number(start + TTL_EXTEND_TO - TTL_THRESHOLD);
+    client.get_grant(&grant_id);
+    assert_eq!(
+        grant_ttls(&env, &client, &grant_key),
+        (TTL_EXTEND_TO, TTL_EXTEND_TO)
+    );
+    // The milestone was not read, so it kept its old TTL until bumped.
+    assert_eq!(grant_ttls(&env, &client, &milestone_key).0, TTL_THRESHOLD);
+    client.bump_grant(&grant_id);
+    assert_eq!(grant_ttls(&env, &client, &milestone_key).0, TTL_EXTEND_TO);
+}
+
+#[test]
+fn test_bump_grant_keeps_ten_year_grant_alive() {
+    const LEDGERS_PER_DAY: u32 = 17_280;
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let token_client = token::Client::new(&env, &token);
+    let grant_id = Symbol::new(&env, "decade");
+    let grant_key = DataKey::Grant(grant_id.clone());
+    let vesting_key = DataKey::Vesting(grant_id.clone());
+    let ten_years: u64 = 10 * 365 * 24 * 60 * 60;
+    client.initialize_grant(
+        &grant_id, &admin, &grantee, &token, &1_000_000, &ten_years, &0,
+    );
+
+    // An untouched grant is only kept alive by permissionless bumps, here
+    // every 160 days with a caller that holds no role in the grant.
+    let mut sequence = 0;
+    while (sequence as u64) * 5 < ten_years {
+        sequence += 160 * LEDGERS_PER_DAY;
+        env.ledger().set_sequence_number(sequence);
+        env.ledger().set_timestamp(sequence as u64 * 5);
+        assert!(grant_ttls(&env, &client, &grant_key).0 > 0);
+        assert!(grant_ttls(&env, &client, &vesting_key).0 > 0);
+        client.bump_grant(&grant_id);
+        assert_eq!(
+            grant_ttls(&env, &client, &grant_key),
+            (TTL_EXTEND_TO, TTL_EXTEND_TO)
+        );
+        assert_eq!(grant_ttls(&env, &client, &vesting_key).0, TTL_EXTEND_TO);
+    }
+
+    assert_eq!(client.claim(&grant_id), 1_000_000);
+    assert_eq!(token_client.balance(&grantee), 1_000_000);
+}
+
+#[test]
+fn test_bump_grant_extends_index_pages() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let multisig = Address::generate(&env);
+    for n in 0..65 {
+        client.create_grant(&numbered(&env, n), &admin, &grantee, &token, &100);
+    }
+    let grant_id = numbered(&env, 0);
+    client.propose_grantee_change(&grant_id, &multisig);
+    client.confirm_grantee_change(&grant_id);
+    let pages = [
+        DataKey::IndexPage(GrantIndex::All, 0),
+        DataKey::IndexPage(GrantIndex::Admin(admin.clone()), 0),
+        DataKey::IndexPage(GrantIndex::Grantee(grantee.clone()), 0),
+        DataKey::IndexPage(GrantIndex::Status(GrantStatus::Active), 0),
+        DataKey::Index(GrantIndex::Grantee(multisig.clone())),
+        DataKey::Listings(grant_id.clone()),
+    ];
+
+    env.ledger()
+        .set_sequence_number(TTL_EXTEND_TO - TTL_THRESHOLD + 1);
+    client.bump_grant(&grant_id);
+    for key in &pages {
+        assert_eq!(grant_ttls(&env, &client, key).0, TTL_EXTEND_TO);
+    }
+}
+
+#[test]
+fn test_archived_grant_is_restored_on_access() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let grant_id = Symbol::new(&env, "archived");
+    let m1 = Symbol::new(&env, "m1");
+    let grant_key = DataKey::Grant(grant_id.clone());
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000);
+    client.add_milestone(&grant_id, &m1, &400, &String::from_str(&env, "Phase 1"));
+
+    // Let every entry, including the contract instance, expire.
+    let start = env.ledger().sequence();
+    env.ledger().set_sequence_number(start + TTL_EXTEND_TO + 1);
+
+    // Access restores the archived entries, and the touch extends them again.
+    assert_eq!(client.get_grant(&grant_id).total_amount, 1_000);
+    assert_eq!(
+        grant_ttls(&env, &client, &grant_key),
+        (TTL_EXTEND_TO, TTL_EXTEND_TO)
+    );
+    submit(&client, &env, &grant_id, &m1);
+    client.approve_milestone(&grant_id, &m1);
+    assert_eq!(client.get_remaining_amount(&grant_id), 600);
+}
+
+/// This crate built for `wasm32v1-none`, standing in for the next release;
+/// `cargo build --target wasm32v1-none --release` has to run first.
+mod release {
+    soroban_sdk::contractimport!(file = "../../target/wasm32v1-none/release/grant_contracts.wasm");
+}
+
+/// Executable recorded in the contract's instance entry.
+fn executable(env: &Env, contract: &Address) -> xdr::ContractExecutable {
+    let contract = xdr::ScAddress::from(contract);
+    env.to_ledger_snapshot()
+        .ledger_entries
+        .into_iter()
+        .find_map(|(_, (entry, _))| match entry.data {
+            xdr::LedgerEntryData::ContractData(data)
+                if data.contract == contract
+                    && data.key == xdr::ScVal::LedgerKeyContractInstance =>
+            {
+                match data.val {
+                    xdr::ScVal::ContractInstance(instance) => Some(instance.executable),
+                    _ => None,
+                }
+            }
+            _ => None,
+        })
+        .unwrap()
+}
+
+#[test]
+fn test_upgrade_and_migrate_require_contract_admin() {
+    let env = Env::default();
+    let (client, admin, _grantee, _token) = setup(&env);
+    assert_eq!(client.admin(), admin);
+    assert_eq!(client.schema_version(), SCHEMA_VERSION);
+    let hash = env.deployer().upload_contract_wasm(release::WASM);
+
+    env.set_auths(&[]);
+    assert!(client.try_upgrade(&hash).is_err());
+    assert!(client.try_migrate().is_err());
+}
+
+#[test]
+fn test_upgrade_and_migrate_keep_grants() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let token_client = token::Client::new(&env, &token);
+    let grant_id = Symbol::new(&env, "kept");
+    let m1 = Symbol::new(&env, "m1");
+    let m2 = Symbol::new(&env, "m2");
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000);
+    client.add_milestone(&grant_id, &m1, &400, &String::from_str(&env, "Alpha"));
+    client.add_milestone(&grant_id, &m2, &300, &String::from_str(&env, "Beta"));
+    submit(&client, &env, &grant_id, &m1);
+    client.approve_milestone(&grant_id, &m1);
+    let grant = client.get_grant(&grant_id);
+
+    let hash = env.deployer().upload_contract_wasm(release::WASM);
+    client.upgrade(&hash);
+    assert_eq!(
+        executable(&env, &client.address),
+        xdr::ContractExecutable::Wasm(xdr::Hash(hash.to_array()))
+    );
+    assert_eq!(client.migrate(), SCHEMA_VERSION);
+    assert_eq!(client.schema_version(), SCHEMA_VERSION);
+
+    // The new code reads the data the old code wrote.
+    assert_eq!(client.admin(), admin);
+    assert_eq!(client.admin_delay(), ADMIN_DELAY);
+    assert_eq!(client.get_grant(&grant_id), grant);
+    assert_eq!(
+        list_all(&client, &GrantIndex::Grantee(grantee.clone())),
+        vec![&env, grant_id.clone()]
+    );
+    submit(&client, &env, &grant_id, &m2);
+    client.approve_milestone(&grant_id, &m2);
+    assert_eq!(token_client.balance(&grantee), 700);
+    assert_eq!(client.get_remaining_amount(&grant_id), 300);
+}
+
+#[test]
+fn test_role_management() {
+    let env = Env::default();
+    let (client, admin, _grantee, _token) = setup(&env);
+    let manager = Address::generate(&env);
+    let deputy = Address::generate(&env);
+
+    // The contract admin holds every role without being granted any.
+    assert!(client.has_role(&Role::SuperAdmin, &admin));
+    assert!(client.has_role(&Role::Pauser, &admin));
+    assert!(!client.has_role(&Role::ProgramManager, &manager));
+
+    client.grant_role(&admin, &Role::ProgramManager, &manager);
+    assert!(client.has_role(&Role::ProgramManager, &manager));
+    assert!(!client.has_role(&Role::Reviewer, &manager));
+    assert_eq!(
+        client.try_grant_role(&manager, &Role::Reviewer, &manager),
+        Err(Ok(Error::NotAuthorized))
+    );
+
+    // A SuperAdmin holds every other role and can manage them.
+    client.grant_role(&admin, &Role::SuperAdmin, &deputy);
+    assert!(client.has_role(&Role::Treasurer, &deputy));
+    client.revoke_role(&deputy, &Role::ProgramManager, &manager);
+    assert!(!client.has_role(&Role::ProgramManager, &manager));
+    client.revoke_role(&deputy, &Role::SuperAdmin, &admin);
+    assert!(client.has_role(&Role::SuperAdmin, &admin));
+
+    client.revoke_role(&admin, &Role::SuperAdmin, &deputy);
+    assert!(!client.has_role(&Role::Treasurer, &deputy));
+    assert_eq!(
+        client.try_revoke_role(&deputy, &Role::SuperAdmin, &admin),
+        Err(Ok(Error::NotAuthorized))
+    );
+}
+
+#[test]
+fn test_entrypoints_are_role_gated() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let manager = Address::generate(&env);
+    let reviewer = Address::generate(&env);
+    let pauser = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &token).mint(&manager, &1_000);
+    let grant_id = Symbol::new(&env, "roles");
+    let m1 = Symbol::new(&env, "m1");
+
+    assert_eq!(
+        client.try_create_grant(&grant_id, &manager, &grantee, &token, &1_000),
+        Err(Ok(Error::NotAuthorized))
+    );
+    client.grant_role(&admin, &Role::ProgramManager, &manager);
+    client.create_grant(&grant_id, &manager, &grantee, &token, &1_000);
+    client.add_milestone(&grant_id, &m1, &400, &String::from_str(&env, "Phase 1"));
+
+    // Roles only reach the grants their holder administers.
+    submit(&client, &env, &grant_id, &m1);
+    client.grant_role(&admin, &Role::Reviewer, &reviewer);
+    assert_eq!(
+        client.try_start_review(&grant_id, &m1, &reviewer),
+        Err(Ok(Error::NotAuthorized))
+    );
+    client.grant_role(&admin, &Role::Pauser, &pauser);
+    assert_eq!(
+        client.try_pause_grant(&grant_id, &pauser),
+        Err(Ok(Error::NotAuthorized))
+    );
+    assert_eq!(
+        client.try_pause_grant(&grant_id, &manager),
+        Err(Ok(Error::NotAuthorized))
+    );
+    client.grant_role(&admin, &Role::Pauser, &manager);
+    client.pause_grant(&grant_id, &manager);
+    client.resume_grant(&grant_id, &manager, &false);
+
+    // The contract admin acts on every grant.
+    client.start_review(&grant_id, &m1, &admin);
+
+    // Acting as the grant admin needs the role for the action too.
+    assert_eq!(
+        client.try_approve_milestone(&grant_id, &m1),
+        Err(Ok(Error::NotAuthorized))
+    );
+    client.grant_role(&admin, &Role::Reviewer, &manager);
+    client.approve_milestone(&grant_id, &m1);
+    assert_eq!(
+        client.try_cancel_grant(&grant_id),
+        Err(Ok(Error::NotAuthorized))
+    );
+    client.grant_role(&admin, &Role::Treasurer, &manager);
+    client.cancel_grant(&grant_id);
+
+    client.revoke_role(&admin, &Role::ProgramManager, &manager);
+    assert_eq!(
+        client.try_create_grant(&Symbol::new(&env, "again"), &manager, &grantee, &token, &1),
+        Err(Ok(Error::NotAuthorized))
+    );
+}
+
+#[test]
+fn test_admin_handover_is_timelocked() {
+    let env = Env::default();
+    let (client, admin, _grantee, _token) = setup(&env);
+    let successor = Address::generate(&env);
+    assert_eq!(client.admin_delay(), ADMIN_DELAY);
+    assert_eq!(client.get_pending_admin(), None);
+    assert_eq!(client.try_accept_admin(), Err(Ok(Error::NoPendingAdmin)));
+
+    env.ledger().set_timestamp(1_000);
+    let pending = client.propose_admin(&successor);
+    assert_eq!(pending.effective_at, 1_000 + ADMIN_DELAY);
+    assert_eq!(client.get_pending_admin(), Some(pending));
+
+    env.ledger().set_timestamp(1_000 + ADMIN_DELAY - 1);
+    assert_eq!(
+        client.try_accept_admin(),
+        Err(Ok(Error::TimelockNotElapsed))
+    );
+    assert_eq!(client.admin(), admin);
+
+    env.ledger().set_timestamp(1_000 + ADMIN_DELAY);
+    client.accept_admin();
+    assert_eq!(client.admin(), successor);
+    assert_eq!(client.get_pending_admin(), None);
+    assert!(client.has_role(&Role::SuperAdmin, &successor));
+    assert!(!client.has_role(&Role::SuperAdmin, &admin));
+}
+
+#[test]
+fn test_admin_handover_can_be_cancelled() {
+    let env = Env::default();
+    let (client, admin, _grantee, _token) = setup(&env);
+    let typo = Address::generate(&env);
+    let successor = Address::generate(&env);
+    assert_eq!(
+        client.try_cancel_admin_proposal(),
+        Err(Ok(Error::NoPendingAdmin))
+    );
+
+    client.propose_admin(&typo);
+    client.cancel_admin_proposal();
+    env.ledger().set_timestamp(ADMIN_DELAY);
+    assert_eq!(client.try_accept_admin(), Err(Ok(Error::NoPendingAdmin)));
+
+    // A new proposal replaces the old one and restarts the delay.
+    client.propose_admin(&typo);
+    client.propose_admin(&successor);
+    assert_eq!(client.get_pending_admin().unwrap().admin, successor);
+    assert_eq!(
+        client.try_accept_admin(),
+        Err(Ok(Error::TimelockNotElapsed))
+    );
+    assert_eq!(client.admin(), admin);
+
+    // Only the proposed admin can accept.
+    env.ledger().set_timestamp(2 * ADMIN_DELAY);
+    env.set_auths(&[]);
+    assert!(client.try_accept_admin().is_err());
+}
+
+#[test]
+fn test_grant_admin_handover_is_timelocked() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let successor = Address::generate(&env);
+    let grant_id = Symbol::new(&env, "handover");
+    let kept = Symbol::new(&env, "kept");
+    let m1 = Symbol::new(&env, "m1");
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000);
+    client.create_grant(&kept, &admin, &grantee, &token, &100);
+    client.add_milestone(&grant_id, &m1, &400, &String::from_str(&env, "Phase 1"));
+    assert_eq!(
+        client.try_accept_grant_admin(&grant_id),
+        Err(Ok(Error::NoPendingAdmin))
+    );
+
+    env.ledger().set_timestamp(1_000);
+    let pending = client.propose_grant_admin(&grant_id, &successor);
+    assert_eq!(pending.effective_at, 1_000 + ADMIN_DELAY);
+    assert_eq!(client.get_pending_grant_admin(&grant_id), Some(pending));
+
+    // The successor has to be a `ProgramManager` and wait out the delay.
+    env.ledger().set_timestamp(1_000 + ADMIN_DELAY - 1);
+    assert_eq!(
+        client.try_accept_grant_admin(&grant_id),
+        Err(Ok(Error::NotAuthorized))
+    );
+    client.grant_role(&admin, &Role::ProgramManager, &successor);
+    assert_eq!(
+        client.try_accept_grant_admin(&grant_id),
+        Err(Ok(Error::TimelockNotElapsed))
+    );
+    assert_eq!(client.get_grant(&grant_id).admin, admin);
+
+    env.ledger().set_timestamp(1_000 + ADMIN_DELAY);
+    client.accept_grant_admin(&grant_id);
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [GrantAdminChanged {
+            grant_id: grant_id.clone(),
+            previous: admin.clone(),
+            admin: successor.clone(),
+        }
+        .to_xdr(&env, &client.address)]
+    );
+    assert_eq!(client.get_grant(&grant_id).admin, successor);
+    assert_eq!(client.get_pending_grant_admin(&grant_id), None);
+    assert_eq!(
+        list_all(&client, &GrantIndex::Admin(admin.clone())),
+        vec![&env, kept.clone()]
+    );
+    assert_eq!(
+        list_all(&client, &GrantIndex::Admin(successor.clone())),
+        vec![&env, grant_id.clone()]
+    );
+    client.remove_milestone(&grant_id, &m1);
+
+    // Handing the grant back lists it under its old entry, not a second one.
+    client.propose_grant_admin(&grant_id, &admin);
+    env.ledger().set_timestamp(1_000 + 2 * ADMIN_DELAY);
+    client.accept_grant_admin(&grant_id);
+    assert_eq!(
+        list_all(&client, &GrantIndex::Admin(admin)),
+        vec![&env, grant_id, kept]
+    );
+    assert_eq!(
+        list_all(&client, &GrantIndex::Admin(successor)),
+        Vec::new(&env)
+    );
+}
+
+#[test]
+fn test_grant_admin_handover_can_be_cancelled() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let typo = Address::generate(&env);
+    let arbiter = Address::generate(&env);
+    let grant_id = Symbol::new(&env, "handover");
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000);
+    client.set_arbiter(&grant_id, &arbiter);
+    assert_eq!(
+        client.try_cancel_grant_admin_proposal(&grant_id),
+        Err(Ok(Error::NoPendingAdmin))
+    );
+
+    client.propose_grant_admin(&grant_id, &typo);
+    client.cancel_grant_admin_proposal(&grant_id);
+    env.ledger().set_timestamp(ADMIN_DELAY);
+    assert_eq!(
+        client.try_accept_grant_admin(&grant_id),
+        Err(Ok(Error::NoPendingAdmin))
+    );
+
+    // The arbiter cannot take the grant over.
+    client.grant_role(&admin, &Role::ProgramManager, &arbiter);
+    client.propose_grant_admin(&grant_id, &arbiter);
+    env.ledger().set_timestamp(2 * ADMIN_DELAY);
+    assert_eq!(
+        client.try_accept_grant_admin(&grant_id),
+        Err(Ok(Error::InvalidArbiter))
+    );
+
+    // Only the proposed admin can accept.
+    client.propose_grant_admin(&grant_id, &typo);
+    client.grant_role(&admin, &Role::ProgramManager, &typo);
+    env.ledger().set_timestamp(3 * ADMIN_DELAY);
+    env.set_auths(&[]);
+    assert!(client.try_accept_grant_admin(&grant_id).is_err());
+    assert_eq!(client.get_grant(&grant_id).admin, admin);
+}
+
+#[test]
+fn test_grantee_rotation_needs_admin_confirmation() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let token_client = token::Client::new(&env, &token);
+    let multisig = Address::generate(&env);
+    let grant_id = Symbol::new(&env, "rotate");
+    let m1 = Symbol::new(&env, "m1");
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000);
+    client.add_milestone(&grant_id, &m1, &400, &String::from_str(&env, "Phase 1"));
+    assert_eq!(
+        client.try_confirm_grantee_change(&grant_id),
+        Err(Ok(Error::NoPendingGranteeChange))
+    );
+
+    client.propose_grantee_change(&grant_id, &multisig);
+    assert_eq!(
+        client.get_pending_grantee(&grant_id),
+        Some(multisig.clone())
+    );
+    assert_eq!(client.get_grant(&grant_id).grantee, grantee);
+
+    client.confirm_grantee_change(&grant_id);
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [GranteeChanged {
+            grant_id: grant_id.clone(),
+            previous: grantee.clone(),
+            grantee: multisig.clone(),
+        }
+        .to_xdr(&env, &client.address)]
+    );
+    assert_eq!(client.get_grant(&grant_id).grantee, multisig);
+    assert_eq!(client.get_pending_grantee(&grant_id), None);
+
+    submit(&client, &env, &grant_id, &m1);
+    client.approve_milestone(&grant_id, &m1);
+    assert_eq!(token_client.balance(&multisig), 400);
+    assert_eq!(token_client.balance(&grantee), 0);
+
+    // Only the current grantee can propose, and only the admin can confirm.
+    env.set_auths(&[]);
+    assert!(client
+        .try_propose_grantee_change(&grant_id, &grantee)
+        .is_err());
+    env.mock_all_auths();
+    client.propose_grantee_change(&grant_id, &grantee);
+    env.set_auths(&[]);
+    assert!(client.try_confirm_grantee_change(&grant_id).is_err());
+}
+
+#[test]
+fn test_payout_address_receives_releases_and_claims() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let token_client = token::Client::new(&env, &token);
+    let treasury = Address::generate(&env);
+    let milestones = Symbol::new(&env, "payout");
+    let stream = Symbol::new(&env, "stream");
+    let m1 = Symbol::new(&env, "m1");
+    client.create_grant(&milestones, &admin, &grantee, &token, &1_000);
+    client.add_milestone(&milestones, &m1, &400, &String::from_str(&env, "Phase 1"));
+    client.initialize_grant(&stream, &admin, &grantee, &token, &1_000, &1_000, &0);
+    assert_eq!(client.get_payout_address(&milestones), grantee);
+
+    client.set_payout_address(&milestones, &Some(treasury.clone()));
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [PayoutChanged {
+            grant_id: milestones.clone(),
+            to: treasury.clone(),
+        }
+        .to_xdr(&env, &client.address)]
+    );
+    client.set_payout_address(&stream, &Some(treasury.clone()));
+    assert_eq!(client.get_payout_address(&milestones), treasury);
+
+    submit(&client, &env, &milestones, &m1);
+    client.approve_milestone(&milestones, &m1);
+    env.ledger().set_timestamp(250);
+    assert_eq!(client.claim(&stream), 250);
+    assert_eq!(token_client.balance(&treasury), 650);
+    assert_eq!(token_client.balance(&grantee), 0);
+
+    // Cancellation settles to the payout address as well.
+    env.ledger().set_timestamp(500);
+    assert_eq!(client.cancel_grant(&stream), 250);
+    assert_eq!(token_client.balance(&treasury), 900);
+
+    client.set_payout_address(&milestones, &None);
+    assert_eq!(client.get_payout_address(&milestones), grantee);
+
+    // A rotation drops the previous grantee's payout address.
+    let successor = Address::generate(&env);
+    client.set_payout_address(&milestones, &Some(treasury.clone()));
+    client.propose_grantee_change(&milestones, &successor);
+    client.confirm_grantee_change(&milestones);
+    assert_eq!(client.get_payout_address(&milestones), successor);
+}
+
+const BATCH_IDS: [&str; 11] = [
+    "b0", "b1", "b2", "b3", "b4", "b5", "b6", "b7", "b8", "b9", "b10",
+];
+
+/// A grant of `total_amount` with `milestones` milestones of 100 each.
+fn grant_spec(
+    env: &Env,
+    grant_id: &str,
+    admin: &Address,
+    grantee: &Address,
+    token: &Address,
+    total_amount: i128,
+    milestones: usize,
+) -> GrantSpec {
+    let mut specs = Vec::new(env);
+    for milestone_id in &BATCH_IDS[..milestones] {
+        specs.push_back(MilestoneSpec {
+            milestone_id: Symbol::new(env, milestone_id),
+            amount: 100,
+            description: String::from_str(env, "Deliverable"),
+        });
+    }
+    GrantSpec {
+        grant_id: Symbol::new(env, grant_id),
+        admin: admin.clone(),
+        grantee: grantee.clone(),
+        token: token.clone(),
+        total_amount,
+        milestones: specs,
+    }
+}
+
+#[test]
+fn test_full_batches_fit_in_one_invocation() {
+    let env = Env::default();
+    let (client, admin, _grantee, token) = setup(&env);
+    let token_client = token::Client::new(&env, &token);
+    let size = MAX_BATCH_SIZE as usize;
+    let milestones = MAX_BATCH_MILESTONES as usize;
+
+    // A funding round: one program manager, a different grantee per grant.
+    let manager = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &token).mint(&manager, &ADMIN_BALANCE);
+    client.grant_role(&admin, &Role::ProgramManager, &manager);
+    client.grant_role(&admin, &Role::Reviewer, &manager);
+    let mut grants = Vec::new(&env);
+    for (i, grant_id) in BATCH_IDS[..size].iter().enumerate() {
+        let count = milestones / size + usize::from(i < milestones % size);
+        grants.push_back(grant_spec(
+            &env,
+            grant_id,
+            &manager,
+            &Address::generate(&env),
+            &token,
+            1_000,
+            count,
+        ));
+    }
+
+    // The test environment enforces mainnet's per-transaction limits.
+    client.create_grants_batch(&grants);
+    assert!(env.cost_estimate().resources().write_entries <= 50);
+    assert_eq!(
+        token_client.balance(&client.address),
+        1_000 * MAX_BATCH_SIZE as i128
+    );
+    let m0 = Symbol::new(&env, "b0");
+    let mut approvals = Vec::new(&env);
+    let mut created = 0;
+    for spec in grants.iter() {
+        let grant = client.get_grant(&spec.grant_id);
+        created += grant.milestones.len();
+        assert_eq!(grant.allocated_amount, 100 * grant.milestones.len() as i128);
+        submit(&client, &env, &spec.grant_id, &m0);
+        approvals.push_back((spec.grant_id, m0.clone()));
+    }
+    assert_eq!(created, MAX_BATCH_MILESTONES);
+
+    client.approve_milestones_batch(&approvals);
+    for spec in grants.iter() {
+        assert_eq!(token_client.balance(&spec.grantee), 100);
+        assert_eq!(
+            client.get_milestone(&spec.grant_id, &m0).status,
+            MilestoneStatus::Paid
+        );
+    }
+}
+
+#[test]
+fn test_create_grants_batch_is_atomic() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let token_client = token::Client::new(&env, &token);
+    let first = grant_spec(&env, "b0", &admin, &grantee, &token, 1_000, 2);
+    let batch = |specs: &[GrantSpec]| {
+        let mut grants = Vec::new(&env);
+        for spec in specs {
+            grants.push_back(spec.clone());
+        }
+        client.try_create_grants_batch(&grants)
+    };
+
+    let duplicate = grant_spec(&env, "b0", &admin, &grantee, &token, 500, 0);
+    let ok = grant_spec(&env, "b1", &admin, &grantee, &token, 500, 0);
+    assert_eq!(
+        batch(&[first.clone(), ok.clone(), duplicate]),
+        Err(Ok(batch_error(2, Error::AlreadyExists)))
+    );
+    assert_eq!(
+        client.try_get_grant(&first.grant_id),
+        Err(Ok(Error::GrantNotFound))
+    );
+    assert_eq!(token_client.balance(&admin), ADMIN_BALANCE);
+
+    let overcommitted = grant_spec(&env, "b2", &admin, &grantee, &token, 250, 3);
+    assert_eq!(
+        batch(&[first.clone(), overcommitted]),
+        Err(Ok(batch_error(1, Error::InvalidAmount)))
+    );
+
+    // Funding is checked against the admin's balance for the whole batch.
+    let half = grant_spec(&env, "b2", &admin, &grantee, &token, ADMIN_BALANCE / 2, 0);
+    let rest = grant_spec(&env, "b3", &admin, &grantee, &token, ADMIN_BALANCE / 2, 0);
+    assert_eq!(
+        batch(&[first.clone(), half.clone(), rest]),
+        Err(Ok(batch_error(2, Error::InsufficientBalance)))
+    );
+
+    let mut oversized = Vec::new(&env);
+    for grant_id in BATCH_IDS {
+        oversized.push_back(grant_spec(&env, grant_id, &admin, &grantee, &token, 1, 0));
+    }
+    assert_eq!(
+        client.try_create_grants_batch(&oversized),
+        Err(Ok(batch_error(MAX_BATCH_SIZE, Error::BatchTooLarge)))
+    );
+
+    // Admins must be program managers.
+    let outsider = Address::generate(&env);
+    let foreign = grant_spec(&env, "b4", &outsider, &grantee, &token, 1, 0);
+    assert_eq!(
+        batch(&[first.clone(), foreign]),
+        Err(Ok(batch_error(1, Error::NotAuthorized)))
+    );
+
+    assert_eq!(batch(&[first.clone(), ok, half]), Ok(Ok(())));
+    assert_eq!(client.get_unallocated_amount(&first.grant_id), 800);
+    assert_eq!(token_client.balance(&admin), ADMIN_BALANCE / 2 - 1_500);
+}
+
+#[test]
+fn test_approve_milestones_batch_is_atomic() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let token_client = token::Client::new(&env, &token);
+    let grant_id = Symbol::new(&env, "approve");
+    let m1 = Symbol::new(&env, "m1");
+    let m2 = Symbol::new(&env, "m2");
+    client.create_grant(&grant_id, &admin, &grantee, &token, &1_000);
+    client.add_milestone(&grant_id, &m1, &400, &String::from_str(&env, "Phase 1"));
+    client.add_milestone(&grant_id, &m2, &300, &String::from_str(&env, "Phase 2"));
+    submit(&client, &env, &grant_id, &m1);
+
+    let pending = vec![
+        &env,
+        (grant_id.clone(), m1.clone()),
+        (grant_id.clone(), m2.clone()),
+    ];
+    assert_eq!(
+        client.try_approve_milestones_batch(&pending),
+        Err(Ok(batch_error(1, Error::InvalidMilestoneState)))
+    );
+    let repeated = vec![
+        &env,
+        (grant_id.clone(), m1.clone()),
+        (grant_id.clone(), m1.clone()),
+    ];
+    assert_eq!(
+        client.try_approve_milestones_batch(&repeated),
+        Err(Ok(batch_error(1, Error::AlreadyReleased)))
+    );
+    assert_eq!(
+        client.get_milestone(&grant_id, &m1).status,
+        MilestoneStatus::Submitted
+    );
+    assert_eq!(token_client.balance(&grantee), 0);
+
+    submit(&client, &env, &grant_id, &m2);
+    client.approve_milestones_batch(&pending);
+    assert_eq!(token_client.balance(&grantee), 700);
+    assert_eq!(client.get_remaining_amount(&grant_id), 300);
+}
+
+/// Collects every grant id of `index`, following the cursors page by page.
+fn list_all(client: &GrantContractClient, index: &GrantIndex) -> Vec<Symbol> {
+    let mut grant_ids = Vec::new(&client.env);
+    let mut cursor = Some(0);
+    while let Some(position) = cursor {
+        let page = client.list_grants(index, &position, &MAX_PAGE_SIZE);
+        grant_ids.append(&page.grant_ids);
+        cursor = page.next_cursor;
+    }
+    grant_ids
+}
+
+/// Grant ids "g00" to "g99".
+fn numbered(env: &Env, n: u32) -> Symbol {
+    let name = [b'g', b'0' + (n / 10) as u8, b'0' + (n % 10) as u8];
+    Symbol::new(env, core::str::from_utf8(&name).unwrap())
+}
+
+#[test]
+fn test_list_grants_pages_through_indexes() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let other = Address::generate(&env);
+    for n in 0..70 {
+        let holder = if n % 2 == 0 { &grantee } else { &other };
+        client.create_grant(&numbered(&env, n), &admin, holder, &token, &100);
+    }
+
+    let first = client.list_grants(&GrantIndex::All, &0, &100);
+    assert_eq!(first.grant_ids.len(), MAX_PAGE_SIZE);
+    assert_eq!(first.grant_ids.get_unchecked(0), numbered(&env, 0));
+    assert_eq!(first.next_cursor, Some(MAX_PAGE_SIZE));
+    let second = client.list_grants(&GrantIndex::All, &MAX_PAGE_SIZE, &MAX_PAGE_SIZE);
+    assert_eq!(second.grant_ids.len(), 20);
+    assert_eq!(second.grant_ids.get_unchecked(19), numbered(&env, 69));
+    assert_eq!(second.next_cursor, None);
+    assert_eq!(client.list_grants(&GrantIndex::All, &0, &0), first);
+    assert_eq!(
+        client.list_grants(&GrantIndex::All, &70, &10),
+        GrantPage {
+            grant_ids: Vec::new(&env),
+            next_cursor: None,
+        }
+    );
+    assert_eq!(
+        list_all(&client, &GrantIndex::Admin(admin.clone())).len(),
+        70
+    );
+    assert_eq!(
+        list_all(&client, &GrantIndex::Grantee(grantee.clone())).len(),
+        35
+    );
+    assert_eq!(
+        list_all(&client, &GrantIndex::Admin(Address::generate(&env))),
+        Vec::new(&env)
+    );
+
+    // Cancelled grants move from the active to the cancelled list.
+    client.cancel_grant(&numbered(&env, 4));
+    let active = list_all(&client, &GrantIndex::Status(GrantStatus::Active));
+    assert_eq!(active.len(), 69);
+    assert!(!active.contains(numbered(&env, 4)));
+    assert_eq!(
+        list_all(&client, &GrantIndex::Status(GrantStatus::Cancelled)),
+        vec![&env, numbered(&env, 4)]
+    );
+}
+
+#[test]
+fn test_grantee_index_follows_rotation() {
+    let env = Env::default();
+    let (client, admin, grantee, token) = setup(&env);
+    let multisig = Address::generate(&env);
+    let grant_id = Symbol::new(&env, "rotate");
+    let kept = Symbol::new(&env, "kept");
+    client.create_grant(&grant_id, &admin, &grantee, &token, &100);
+    client.create_grant(&kept, &admin, &grantee, &token, &100);
+
+    client.propose_grantee_change(&grant_id, &multisig);
+    client.confirm_grantee_change(&grant_id);
+    assert_eq!(
+        list_all(&client, &GrantIndex::Grantee(grantee.clone())),
+        vec![&env, kept.clone()]
+    );
+    assert_eq!(
+        list_all(&client, &GrantIndex::Grantee(multisig.clone())),
+        vec![&env, grant_id.clone()]
+    );
+
+    // Rotating back lists the grant under its old entry, not a second one.
+    client.propose_grantee_change(&grant_id, &grantee);
+    client.confirm_grantee_change(&grant_id);
+    assert_eq!(
+        list_all(&client, &GrantIndex::Grantee(grantee.clone())),
+        vec![&env, grant_id.clone(), kept]
+    );
+    assert_eq!(
+        list_all(&client, &GrantIndex::Grantee(multisig)),
+        Vec::new(&env)
+    );
+}
@@ -1,22 +1,544 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, vec, Env, String, Vec};
+use soroban_sdk::{
+    contract, contracterror, contractevent, contractimpl, contracttype, token, Address, BytesN,
+    Env, IntoVal, TryFromVal, Val, U256,
+};
+
+/// Contract error codes. Numbers line up with the grant contract where the
+/// meaning is shared, and codes 25 and 26 are specific to the wallet; see
+/// `ERRORS.md`.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    /// Not enough funds to complete the transaction.
+    InsufficientBalance = 2,
+    /// No vesting schedule exists for the beneficiary.
+    ScheduleNotFound = 3,
+    /// The amount must be positive.
+    InvalidAmount = 5,
+    /// The schedule parameters are inconsistent (cliff longer than duration, overflowing end).
+    InvalidSchedule = 11,
+    /// No ownership handover has been proposed.
+    NoPendingAdmin = 21,
+    /// The proposed owner cannot accept before the handover delay has passed.
+    TimelockNotElapsed = 22,
+    /// The schedule was created as non-revocable.
+    NotRevocable = 25,
+    /// The beneficiary already holds `MAX_SCHEDULES_PER_BENEFICIARY` schedules
+    /// that are not fully paid out.
+    TooManySchedules = 26,
+}
+
+/// Upper bound on schedule ids per beneficiary, keeping `release_all` and the
+/// aggregate views within a single invocation's budget. Once a beneficiary
+/// has this many, new deposits reuse the ids of fully paid out schedules.
+pub const MAX_SCHEDULES_PER_BENEFICIARY: u32 = 32;
+
+/// Version of the storage layout written by this build. `migrate` brings
+/// data written by older builds up to it after an `upgrade`.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Ledgers closed per day at the nominal 5 second close time.
+const DAY_IN_LEDGERS: u32 = 17_280;
+
+/// Entries whose TTL is at or below this many ledgers are extended when
+/// touched.
+pub const TTL_THRESHOLD: u32 = 30 * DAY_IN_LEDGERS;
+
+/// Ledgers an entry stays live after being extended, about 180 days.
+pub const TTL_EXTEND_TO: u32 = 180 * DAY_IN_LEDGERS;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataKey {
+    Owner,
+    Token,
+    Treasury,
+    /// Storage layout version; absent for data written before versioning (1).
+    SchemaVersion,
+    /// Seconds a proposed owner must wait before accepting.
+    AdminDelay,
+    /// Ownership handover awaiting acceptance.
+    PendingAdmin,
+    /// Number of schedules created for a beneficiary; ids run from zero.
+    ScheduleCount(Address),
+    Schedule(Address, u32),
+}
+
+/// Tokens locked for one beneficiary, vesting linearly from `start` over
+/// `duration` seconds with nothing releasable before `start + cliff`.
+///
+/// Once revoked, `total_amount` is cut down to what had vested at that moment
+/// and all of it stays releasable.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VestingSchedule {
+    pub total_amount: i128,
+    pub released_amount: i128,
+    pub start: u64,
+    pub cliff: u64,
+    pub duration: u64,
+    pub revocable: bool,
+    pub revoked: bool,
+}
+
+/// An ownership handover started with `propose_admin`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingAdmin {
+    pub admin: Address,
+    /// Ledger time from which `admin` may call `accept_admin`.
+    pub effective_at: u64,
+}
+
+/// Published when the owner revokes a schedule.
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Revoked {
+    #[topic]
+    pub beneficiary: Address,
+    #[topic]
+    pub schedule_id: u32,
+    pub vested_amount: i128,
+    pub refunded_amount: i128,
+}
+
+impl VestingSchedule {
+    /// Whether everything the schedule will ever pay has been released, so
+    /// its id can be reused.
+    pub fn is_finished(&self) -> bool {
+        self.released_amount == self.total_amount
+    }
+
+    /// Cumulative amount vested at `now`.
+    pub fn vested_at(&self, env: &Env, now: u64) -> i128 {
+        if self.revoked {
+            return self.total_amount;
+        }
+        if now < self.start.saturating_add(self.cliff) {
+            return 0;
+        }
+        let elapsed = now - self.start;
+        if elapsed >= self.duration {
+            return self.total_amount;
+        }
+        // total * elapsed / duration in 256 bits so large deposits stay exact.
+        let vested = U256::from_u128(env, self.total_amount as u128)
+            .mul(&U256::from_u128(env, elapsed as u128))
+            .div(&U256::from_u128(env, self.duration as u128));
+        vested.to_u128().unwrap_or_default() as i128
+    }
+}
 
 #[contract]
-pub struct Contract;
-
-// This is a sample contract. Replace this placeholder with your own contract logic.
-// A corresponding test example is available in `test.rs`.
-//
-// For comprehensive examples, visit <https://github.com/stellar/soroban-examples>.
-// The repository includes use cases for the Stellar ecosystem, such as data storage on
-// the blockchain, token swaps, liquidity pools, and more.
-//
-// Refer to the official documentation:
-// <https://developers.stellar.org/docs/build/smart-contracts/overview>.
+pub struct VestingContract;
+
+/// Read a persistent entry, extending its TTL and the contract instance's if
+/// it exists.
+fn read<V: TryFromVal<Env, Val>>(env: &Env, key: &DataKey) -> Option<V> {
+    let value = env.storage().persistent().get(key);
+    if value.is_some() {
+        extend_ttl(env, key);
+    }
+    value
+}
+
+/// Write a persistent entry and extend its TTL and the contract instance's.
+fn write<V: IntoVal<Env, Val>>(env: &Env, key: &DataKey, value: &V) {
+    env.storage().persistent().set(key, value);
+    extend_ttl(env, key);
+}
+
+fn extend_ttl(env: &Env, key: &DataKey) {
+    env.storage()
+        .persistent()
+        .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
+    env.storage()
+        .instance()
+        .extend_ttl(TTL_THRESHOLD, TTL_EXTEND_TO);
+}
+
+fn owner(env: &Env) -> Address {
+    env.storage().instance().get(&DataKey::Owner).unwrap()
+}
+
+fn token(env: &Env) -> Address {
+    env.storage().instance().get(&DataKey::Token).unwrap()
+}
+
+fn treasury(env: &Env) -> Address {
+    env.storage().instance().get(&DataKey::Treasury).unwrap()
+}
+
+fn schedule_count(env: &Env, beneficiary: &Address) -> u32 {
+    read(env, &DataKey::ScheduleCount(beneficiary.clone())).unwrap_or(0)
+}
+
+fn load_schedule(
+    env: &Env,
+    beneficiary: &Address,
+    schedule_id: u32,
+) -> Result<VestingSchedule, Error> {
+    read(env, &DataKey::Schedule(beneficiary.clone(), schedule_id)).ok_or(Error::ScheduleNotFound)
+}
+
+fn save_schedule(env: &Env, beneficiary: &Address, schedule_id: u32, schedule: &VestingSchedule) {
+    write(
+        env,
+        &DataKey::Schedule(beneficiary.clone(), schedule_id),
+        schedule,
+    );
+}
+
+/// Id for a new schedule of `beneficiary`: the next unused one, or once
+/// `MAX_SCHEDULES_PER_BENEFICIARY` are taken, the first finished schedule's.
+fn next_schedule_id(env: &Env, beneficiary: &Address) -> Result<u32, Error> {
+    let count = schedule_count(env, beneficiary);
+    if count < MAX_SCHEDULES_PER_BENEFICIARY {
+        return Ok(count);
+    }
+    (0..count)
+        .find(|&schedule_id| {
+            load_schedule(env, beneficiary, schedule_id)
+                .is_ok_and(|schedule| schedule.is_finished())
+        })
+        .ok_or(Error::TooManySchedules)
+}
+
+/// Mark everything vested on `schedule` as released and return the newly released amount.
+fn settle(env: &Env, schedule: &mut VestingSchedule) -> i128 {
+    let vested = schedule.vested_at(env, env.ledger().timestamp());
+    let amount = vested - schedule.released_amount;
+    schedule.released_amount = vested;
+    amount
+}
+
+fn pay_out(env: &Env, to: &Address, amount: i128) {
+    token::Client::new(env, &token(env)).transfer(&env.current_contract_address(), to, &amount);
+}
+
 #[contractimpl]
-impl Contract {
-    pub fn hello(env: Env, to: String) -> Vec<String> {
-        vec![&env, String::from_str(&env, "Hello"), to]
+impl VestingContract {
+    /// Set up a wallet that vests `token` deposited by `owner`. Unvested
+    /// tokens of revoked schedules are returned to `treasury`.
+    ///
+    /// `admin_delay` is the number of seconds a new owner must wait between
+    /// `propose_admin` and `accept_admin`, fixed for the life of the wallet.
+    pub fn __constructor(
+        env: Env,
+        owner: Address,
+        token: Address,
+        treasury: Address,
+        admin_delay: u64,
+    ) {
+        env.storage().instance().set(&DataKey::Owner, &owner);
+        env.storage()
+            .instance()
+            .set(&DataKey::AdminDelay, &admin_delay);
+        env.storage().instance().set(&DataKey::Token, &token);
+        env.storage().instance().set(&DataKey::Treasury, &treasury);
+        env.storage()
+            .instance()
+            .set(&DataKey::SchemaVersion, &SCHEMA_VERSION);
+        env.storage()
+            .instance()
+            .extend_ttl(TTL_THRESHOLD, TTL_EXTEND_TO);
+    }
+
+    /// Replace the contract code with the uploaded WASM `new_wasm_hash`,
+    /// keeping its address and storage. Owner only.
+    ///
+    /// Call `migrate` with the new code afterwards if its `SCHEMA_VERSION`
+    /// is higher.
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
+        owner(&env).require_auth();
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
+    /// Bring storage written by an older build up to `SCHEMA_VERSION` after
+    /// an `upgrade`, and return the version afterwards. Owner only.
+    ///
+    /// Every build so far writes the first layout, so there is nothing to
+    /// convert yet; a build that changes the layout adds its conversion here.
+    pub fn migrate(env: Env) -> u32 {
+        owner(&env).require_auth();
+        env.storage()
+            .instance()
+            .set(&DataKey::SchemaVersion, &SCHEMA_VERSION);
+        SCHEMA_VERSION
+    }
+
+    /// Storage layout version the contract's data is in.
+    pub fn schema_version(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::SchemaVersion)
+            .unwrap()
+    }
+
+    /// Lock `amount` tokens from the owner in a new schedule for
+    /// `beneficiary` and return its id. Owner only.
+    ///
+    /// A beneficiary may hold up to `MAX_SCHEDULES_PER_BENEFICIARY`
+    /// schedules; past that, the new schedule replaces one that has been
+    /// fully paid out and takes over its id. Only schedules created with
+    /// `revocable` set can later be revoked.
+    pub fn deposit(
+        env: Env,
+        beneficiary: Address,
+        amount: i128,
+        start: u64,
+        cliff: u64,
+        duration: u64,
+        revocable: bool,
+    ) -> Result<u32, Error> {
+        let owner = owner(&env);
+        owner.require_auth();
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        if cliff > duration || start.checked_add(duration).is_none() {
+            return Err(Error::InvalidSchedule);
+        }
+        let schedule_id = next_schedule_id(&env, &beneficiary)?;
+
+        let token_client = token::Client::new(&env, &token(&env));
+        if token_client.balance(&owner) < amount {
+            return Err(Error::InsufficientBalance);
+        }
+        token_client.transfer(&owner, env.current_contract_address(), &amount);
+
+        let schedule = VestingSchedule {
+            total_amount: amount,
+            released_amount: 0,
+            start,
+            cliff,
+            duration,
+            revocable,
+            revoked: false,
+        };
+        save_schedule(&env, &beneficiary, schedule_id, &schedule);
+        if schedule_id == schedule_count(&env, &beneficiary) {
+            write(
+                &env,
+                &DataKey::ScheduleCount(beneficiary.clone()),
+                &(schedule_id + 1),
+            );
+        }
+        Ok(schedule_id)
+    }
+
+    /// Stop vesting `beneficiary`'s schedule `schedule_id` and return the
+    /// unvested remainder to the treasury. Owner only.
+    ///
+    /// The portion vested so far stays releasable by the beneficiary. Revoking
+    /// an already revoked schedule is a no-op. Returns the amount refunded.
+    pub fn revoke(env: Env, beneficiary: Address, schedule_id: u32) -> Result<i128, Error> {
+        owner(&env).require_auth();
+        let mut schedule = load_schedule(&env, &beneficiary, schedule_id)?;
+        if !schedule.revocable {
+            return Err(Error::NotRevocable);
+        }
+        if schedule.revoked {
+            return Ok(0);
+        }
+
+        let vested = schedule.vested_at(&env, env.ledger().timestamp());
+        let refunded = schedule.total_amount - vested;
+        schedule.total_amount = vested;
+        schedule.revoked = true;
+        save_schedule(&env, &beneficiary, schedule_id, &schedule);
+
+        if refunded > 0 {
+            pay_out(&env, &treasury(&env), refunded);
+        }
+        Revoked {
+            beneficiary,
+            schedule_id,
+            vested_amount: vested,
+            refunded_amount: refunded,
+        }
+        .publish(&env);
+        Ok(refunded)
+    }
+
+    /// Transfer everything vested but not yet released on one schedule to
+    /// `beneficiary`.
+    ///
+    /// Returns the amount transferred, which is zero when nothing new has vested.
+    pub fn release(env: Env, beneficiary: Address, schedule_id: u32) -> Result<i128, Error> {
+        beneficiary.require_auth();
+        let mut schedule = load_schedule(&env, &beneficiary, schedule_id)?;
+
+        let amount = settle(&env, &mut schedule);
+        if amount > 0 {
+            save_schedule(&env, &beneficiary, schedule_id, &schedule);
+            pay_out(&env, &beneficiary, amount);
+        }
+        Ok(amount)
+    }
+
+    /// Release every schedule of `beneficiary` in a single transfer.
+    ///
+    /// Returns the total amount transferred.
+    pub fn release_all(env: Env, beneficiary: Address) -> Result<i128, Error> {
+        beneficiary.require_auth();
+        let mut total = 0;
+        for schedule_id in 0..schedule_count(&env, &beneficiary) {
+            let mut schedule = load_schedule(&env, &beneficiary, schedule_id)?;
+            let amount = settle(&env, &mut schedule);
+            if amount > 0 {
+                save_schedule(&env, &beneficiary, schedule_id, &schedule);
+                total += amount;
+            }
+        }
+        if total > 0 {
+            pay_out(&env, &beneficiary, total);
+        }
+        Ok(total)
+    }
+
+    pub fn get_schedule(
+        env: Env,
+        beneficiary: Address,
+        schedule_id: u32,
+    ) -> Result<VestingSchedule, Error> {
+        load_schedule(&env, &beneficiary, schedule_id)
+    }
+
+    /// Number of schedules held by `beneficiary`; valid ids are `0..count`.
+    pub fn schedule_count(env: Env, beneficiary: Address) -> u32 {
+        schedule_count(&env, &beneficiary)
+    }
+
+    /// Extend the TTL of `beneficiary`'s schedules and schedule count, along
+    /// with the contract instance. Callable by anyone.
+    ///
+    /// Releasing already extends the schedules it touches; this keeps a
+    /// schedule nobody releases from for months, such as one with a long
+    /// cliff, from being archived.
+    pub fn bump_schedules(env: Env, beneficiary: Address) {
+        env.storage()
+            .instance()
+            .extend_ttl(TTL_THRESHOLD, TTL_EXTEND_TO);
+        for schedule_id in 0..schedule_count(&env, &beneficiary) {
+            let _ = load_schedule(&env, &beneficiary, schedule_id);
+        }
+    }
+
+    /// Total amount vested so far on one schedule, released or not.
+    pub fn vested_amount(env: Env, beneficiary: Address, schedule_id: u32) -> Result<i128, Error> {
+        let schedule = load_schedule(&env, &beneficiary, schedule_id)?;
+        Ok(schedule.vested_at(&env, env.ledger().timestamp()))
+    }
+
+    /// Amount of one schedule already transferred to the beneficiary.
+    pub fn released_amount(
+        env: Env,
+        beneficiary: Address,
+        schedule_id: u32,
+    ) -> Result<i128, Error> {
+        Ok(load_schedule(&env, &beneficiary, schedule_id)?.released_amount)
+    }
+
+    /// Amount of one schedule that has not vested yet.
+    pub fn locked_amount(env: Env, beneficiary: Address, schedule_id: u32) -> Result<i128, Error> {
+        let schedule = load_schedule(&env, &beneficiary, schedule_id)?;
+        Ok(schedule.total_amount - schedule.vested_at(&env, env.ledger().timestamp()))
+    }
+
+    /// Vested amount summed over all of `beneficiary`'s schedules.
+    pub fn vested_of(env: Env, beneficiary: Address) -> i128 {
+        let now = env.ledger().timestamp();
+        let mut total = 0;
+        for schedule_id in 0..schedule_count(&env, &beneficiary) {
+            if let Ok(schedule) = load_schedule(&env, &beneficiary, schedule_id) {
+                total += schedule.vested_at(&env, now);
+            }
+        }
+        total
+    }
+
+    /// Unvested amount summed over all of `beneficiary`'s schedules.
+    pub fn locked_of(env: Env, beneficiary: Address) -> i128 {
+        let now = env.ledger().timestamp();
+        let mut total = 0;
+        for schedule_id in 0..schedule_count(&env, &beneficiary) {
+            if let Ok(schedule) = load_schedule(&env, &beneficiary, schedule_id) {
+                total += schedule.total_amount - schedule.vested_at(&env, now);
+            }
+        }
+        total
+    }
+
+    pub fn owner(env: Env) -> Address {
+        owner(&env)
+    }
+
+    pub fn token(env: Env) -> Address {
+        token(&env)
+    }
+
+    pub fn treasury(env: Env) -> Address {
+        treasury(&env)
+    }
+
+    /// Start handing ownership over to `new_owner`, who can take over with
+    /// `accept_admin` once the admin delay has passed. Owner only.
+    ///
+    /// A new proposal replaces any pending one and restarts the delay.
+    pub fn propose_admin(env: Env, new_owner: Address) -> PendingAdmin {
+        owner(&env).require_auth();
+        let pending = PendingAdmin {
+            admin: new_owner,
+            effective_at: env
+                .ledger()
+                .timestamp()
+                .saturating_add(Self::admin_delay(env.clone())),
+        };
+        env.storage()
+            .instance()
+            .set(&DataKey::PendingAdmin, &pending);
+        pending
+    }
+
+    /// Complete a handover started with `propose_admin`. Proposed owner only.
+    pub fn accept_admin(env: Env) -> Result<(), Error> {
+        let pending: PendingAdmin = env
+            .storage()
+            .instance()
+            .get(&DataKey::PendingAdmin)
+            .ok_or(Error::NoPendingAdmin)?;
+        pending.admin.require_auth();
+        if env.ledger().timestamp() < pending.effective_at {
+            return Err(Error::TimelockNotElapsed);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::Owner, &pending.admin);
+        env.storage().instance().remove(&DataKey::PendingAdmin);
+        Ok(())
+    }
+
+    /// Withdraw a pending handover. Owner only.
+    pub fn cancel_admin_proposal(env: Env) -> Result<(), Error> {
+        owner(&env).require_auth();
+        if !env.storage().instance().has(&DataKey::PendingAdmin) {
+            return Err(Error::NoPendingAdmin);
+        }
+        env.storage().instance().remove(&DataKey::PendingAdmin);
+        Ok(())
+    }
+
+    pub fn get_pending_admin(env: Env) -> Option<PendingAdmin> {
+        env.storage().instance().get(&DataKey::PendingAdmin)
+    }
+
+    /// Seconds a proposed owner must wait before accepting.
+    pub fn admin_delay(env: Env) -> u64 {
+        env.storage().instance().get(&DataKey::AdminDelay).unwrap()
     }
 }
 
@@ -1,21 +1,452 @@
 #![cfg(test)]
 
 use super::*;
-use soroban_sdk::{vec, Env, String};
+use soroban_sdk::{
+    testutils::{
+        storage::{Instance as _, Persistent as _},
+        Address as _, Events as _, Ledger,
+    },
+    token, xdr, Address, Env, Event,
+};
+
+const OWNER_BALANCE: i128 = 1_000_000_000;
+const ADMIN_DELAY: u64 = 86_400;
+
+fn setup(env: &Env) -> (VestingContractClient<'_>, Address, Address) {
+    env.mock_all_auths();
+    let owner = Address::generate(env);
+    let treasury = Address::generate(env);
+    let issuer = Address::generate(env);
+    let token = env.register_stellar_asset_contract_v2(issuer).address();
+    token::StellarAssetClient::new(env, &token).mint(&owner, &OWNER_BALANCE);
+    let contract_id = env.register(VestingContract, (&owner, &token, &treasury, &ADMIN_DELAY));
+    let client = VestingContractClient::new(env, &contract_id);
+    (client, owner, token)
+}
+
+#[test]
+fn test_deposit_locks_tokens() {
+    let env = Env::default();
+    let (client, owner, token) = setup(&env);
+    let beneficiary = Address::generate(&env);
+
+    client.deposit(&beneficiary, &1_000, &100, &0, &1_000, &false);
+
+    let token_client = token::Client::new(&env, &token);
+    assert_eq!(token_client.balance(&owner), OWNER_BALANCE - 1_000);
+    assert_eq!(token_client.balance(&client.address), 1_000);
+    assert_eq!(client.owner(), owner);
+    assert_eq!(client.token(), token);
+    assert_eq!(
+        client.get_schedule(&beneficiary, &0),
+        VestingSchedule {
+            total_amount: 1_000,
+            released_amount: 0,
+            start: 100,
+            cliff: 0,
+            duration: 1_000,
+            revocable: false,
+            revoked: false,
+        }
+    );
+}
+
+#[test]
+fn test_deposit_validation() {
+    let env = Env::default();
+    let (client, _owner, _token) = setup(&env);
+    let beneficiary = Address::generate(&env);
+
+    assert_eq!(
+        client.try_deposit(&beneficiary, &0, &0, &0, &10, &false),
+        Err(Ok(Error::InvalidAmount))
+    );
+    assert_eq!(
+        client.try_deposit(&beneficiary, &10, &0, &11, &10, &false),
+        Err(Ok(Error::InvalidSchedule))
+    );
+    assert_eq!(
+        client.try_deposit(&beneficiary, &(OWNER_BALANCE + 1), &0, &0, &10, &false),
+        Err(Ok(Error::InsufficientBalance))
+    );
+    for expected_id in 0..MAX_SCHEDULES_PER_BENEFICIARY {
+        assert_eq!(
+            client.deposit(&beneficiary, &10, &0, &0, &10, &false),
+            expected_id
+        );
+    }
+    assert_eq!(
+        client.try_deposit(&beneficiary, &10, &0, &0, &10, &false),
+        Err(Ok(Error::TooManySchedules))
+    );
+    assert_eq!(
+        client.try_release(&Address::generate(&env), &0),
+        Err(Ok(Error::ScheduleNotFound))
+    );
+}
+
+#[test]
+fn test_deposit_reuses_finished_schedule_ids() {
+    let env = Env::default();
+    let (client, _owner, _token) = setup(&env);
+    let beneficiary = Address::generate(&env);
+    for _ in 0..MAX_SCHEDULES_PER_BENEFICIARY {
+        client.deposit(&beneficiary, &10, &0, &0, &100, &true);
+    }
+
+    assert_eq!(
+        client.try_deposit(&beneficiary, &10, &0, &0, &100, &false),
+        Err(Ok(Error::TooManySchedules))
+    );
+
+    // Schedule 3 is revoked with nothing vested; schedule 5 is partly released.
+    client.revoke(&beneficiary, &3);
+    env.ledger().set_timestamp(50);
+    client.release(&beneficiary, &5);
+    assert_eq!(client.deposit(&beneficiary, &7, &100, &0, &100, &false), 3);
+    assert_eq!(client.get_schedule(&beneficiary, &3).total_amount, 7);
+    assert_eq!(
+        client.try_deposit(&beneficiary, &10, &0, &0, &100, &false),
+        Err(Ok(Error::TooManySchedules))
+    );
+    env.ledger().set_timestamp(100);
+    client.release(&beneficiary, &5);
+    assert_eq!(client.deposit(&beneficiary, &8, &100, &0, &100, &false), 5);
+    assert_eq!(
+        client.schedule_count(&beneficiary),
+        MAX_SCHEDULES_PER_BENEFICIARY
+    );
+    assert_eq!(
+        client.try_deposit(&beneficiary, &10, &0, &0, &100, &false),
+        Err(Ok(Error::TooManySchedules))
+    );
+}
+
+#[test]
+fn test_release_follows_cliff_and_linear_schedule() {
+    let env = Env::default();
+    let (client, _owner, token) = setup(&env);
+    let token_client = token::Client::new(&env, &token);
+    let beneficiary = Address::generate(&env);
+
+    client.deposit(&beneficiary, &1_000, &1_000, &250, &1_000, &false);
+
+    env.ledger().set_timestamp(1_249);
+    assert_eq!(client.vested_amount(&beneficiary, &0), 0);
+    assert_eq!(client.locked_amount(&beneficiary, &0), 1_000);
+    assert_eq!(client.release(&beneficiary, &0), 0);
+
+    env.ledger().set_timestamp(1_250);
+    assert_eq!(client.vested_amount(&beneficiary, &0), 250);
+    assert_eq!(client.release(&beneficiary, &0), 250);
+    assert_eq!(client.released_amount(&beneficiary, &0), 250);
+    assert_eq!(client.release(&beneficiary, &0), 0);
+
+    env.ledger().set_timestamp(1_600);
+    assert_eq!(client.locked_amount(&beneficiary, &0), 400);
+    assert_eq!(client.release(&beneficiary, &0), 350);
+
+    env.ledger().set_timestamp(5_000);
+    assert_eq!(client.release(&beneficiary, &0), 400);
+    assert_eq!(client.locked_amount(&beneficiary, &0), 0);
+    assert_eq!(client.released_amount(&beneficiary, &0), 1_000);
+    assert_eq!(token_client.balance(&beneficiary), 1_000);
+    assert_eq!(token_client.balance(&client.address), 0);
+}
+
+#[test]
+fn test_vesting_is_exact_for_large_deposits() {
+    let env = Env::default();
+    let (client, owner, token) = setup(&env);
+    let beneficiary = Address::generate(&env);
+    let total = i128::MAX / 2;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &total);
+
+    let duration: u64 = 315_360_000;
+    client.deposit(&beneficiary, &total, &0, &0, &duration, &false);
+
+    env.ledger().set_timestamp(duration / 2);
+    assert_eq!(client.vested_amount(&beneficiary, &0), total / 2);
+}
+
+#[test]
+fn test_revoke_returns_unvested_tokens_to_treasury() {
+    let env = Env::default();
+    let (client, _owner, token) = setup(&env);
+    let token_client = token::Client::new(&env, &token);
+    let treasury = client.treasury();
+    let beneficiary = Address::generate(&env);
+
+    client.deposit(&beneficiary, &1_000, &0, &0, &1_000, &true);
+    env.ledger().set_timestamp(300);
+    assert_eq!(client.release(&beneficiary, &0), 300);
+
+    env.ledger().set_timestamp(400);
+    assert_eq!(client.revoke(&beneficiary, &0), 600);
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [Revoked {
+            beneficiary: beneficiary.clone(),
+            schedule_id: 0,
+            vested_amount: 400,
+            refunded_amount: 600,
+        }
+        .to_xdr(&env, &client.address)]
+    );
+    assert_eq!(token_client.balance(&treasury), 600);
+
+    // Vesting stops at the revocation, but the vested remainder stays releasable.
+    env.ledger().set_timestamp(900);
+    assert_eq!(client.locked_amount(&beneficiary, &0), 0);
+    assert_eq!(client.vested_amount(&beneficiary, &0), 400);
+    assert_eq!(client.release(&beneficiary, &0), 100);
+    assert_eq!(token_client.balance(&beneficiary), 400);
+    assert_eq!(token_client.balance(&client.address), 0);
+
+    // Revoking again changes nothing and publishes nothing.
+    assert_eq!(client.revoke(&beneficiary, &0), 0);
+    assert_eq!(
+        env.events()
+            .all()
+            .filter_by_contract(&client.address)
+            .events()
+            .len(),
+        0
+    );
+    assert_eq!(token_client.balance(&treasury), 600);
+}
 
 #[test]
-fn test() {
+fn test_non_revocable_schedule_rejects_revoke() {
     let env = Env::default();
-    let contract_id = env.register(Contract, ());
-    let client = ContractClient::new(&env, &contract_id);
+    let (client, _owner, _token) = setup(&env);
+    let beneficiary = Address::generate(&env);
 
-    let words = client.hello(&String::from_str(&env, "Dev"));
+    client.deposit(&beneficiary, &1_000, &0, &0, &1_000, &false);
     assert_eq!(
-        words,
-        vec![
-            &env,
-            String::from_str(&env, "Hello"),
-            String::from_str(&env, "Dev"),
-        ]
+        client.try_revoke(&beneficiary, &0),
+        Err(Ok(Error::NotRevocable))
     );
+    assert!(!client.get_schedule(&beneficiary, &0).revoked);
+}
+
+#[test]
+fn test_multiple_schedules_per_beneficiary() {
+    let env = Env::default();
+    let (client, _owner, token) = setup(&env);
+    let token_client = token::Client::new(&env, &token);
+    let beneficiary = Address::generate(&env);
+    let other = Address::generate(&env);
+
+    assert_eq!(client.schedule_count(&beneficiary), 0);
+    assert_eq!(client.vested_of(&beneficiary), 0);
+
+    let first = client.deposit(&beneficiary, &1_000, &0, &0, &1_000, &false);
+    let second = client.deposit(&beneficiary, &600, &500, &100, &600, &true);
+    assert_eq!((first, second), (0, 1));
+    assert_eq!(client.deposit(&other, &50, &0, &0, &50, &false), 0);
+    assert_eq!(client.schedule_count(&beneficiary), 2);
+
+    env.ledger().set_timestamp(550);
+    assert_eq!(client.vested_amount(&beneficiary, &first), 550);
+    assert_eq!(client.vested_amount(&beneficiary, &second), 0);
+    assert_eq!(client.vested_of(&beneficiary), 550);
+    assert_eq!(client.locked_of(&beneficiary), 450 + 600);
+
+    env.ledger().set_timestamp(700);
+    assert_eq!(client.vested_of(&beneficiary), 700 + 200);
+    assert_eq!(client.release(&beneficiary, &second), 200);
+    assert_eq!(client.release_all(&beneficiary), 700);
+    assert_eq!(client.release_all(&beneficiary), 0);
+
+    env.ledger().set_timestamp(2_000);
+    assert_eq!(client.release_all(&beneficiary), 300 + 400);
+    assert_eq!(client.locked_of(&beneficiary), 0);
+    assert_eq!(token_client.balance(&beneficiary), 1_600);
+
+    // Other beneficiaries are untouched.
+    assert_eq!(client.released_amount(&other, &0), 0);
+    assert_eq!(client.vested_of(&other), 50);
+}
+
+#[test]
+fn test_storage_ttl_is_extended_on_touch() {
+    let env = Env::default();
+    let (client, _owner, _token) = setup(&env);
+    let beneficiary = Address::generate(&env);
+    let schedule_key = DataKey::Schedule(beneficiary.clone(), 0);
+    let count_key = DataKey::ScheduleCount(beneficiary.clone());
+    let ttls = |key: &DataKey| {
+        env.as_contract(&client.address, || {
+            (
+                env.storage().persistent().get_ttl(key),
+                env.storage().instance().get_ttl(),
+            )
+        })
+    };
+
+    client.deposit(&beneficiary, &1_000, &0, &0, &1_000, &false);
+    assert_eq!(ttls(&schedule_key), (TTL_EXTEND_TO, TTL_EXTEND_TO));
+    assert_eq!(ttls(&count_key).0, TTL_EXTEND_TO);
+
+    env.ledger()
+        .set_sequence_number(TTL_EXTEND_TO - TTL_THRESHOLD + 1);
+    assert_eq!(ttls(&schedule_key).0, TTL_THRESHOLD - 1);
+    client.release(&beneficiary, &0);
+    assert_eq!(ttls(&schedule_key), (TTL_EXTEND_TO, TTL_EXTEND_TO));
+
+    // Past expiry the schedule is restored on access and extended again.
+    env.ledger().set_sequence_number(2 * TTL_EXTEND_TO);
+    env.ledger().set_timestamp(1_000);
+    assert_eq!(client.release_all(&beneficiary), 1_000);
+    assert_eq!(ttls(&schedule_key), (TTL_EXTEND_TO, TTL_EXTEND_TO));
+    assert_eq!(ttls(&count_key).0, TTL_EXTEND_TO);
+}
+
+#[test]
+fn test_bump_schedules_keeps_ten_year_schedule_alive() {
+    const LEDGERS_PER_DAY: u32 = 17_280;
+    let env = Env::default();
+    let (client, _owner, token) = setup(&env);
+    let token_client = token::Client::new(&env, &token);
+    let beneficiary = Address::generate(&env);
+    let schedule_key = DataKey::Schedule(beneficiary.clone(), 0);
+    let count_key = DataKey::ScheduleCount(beneficiary.clone());
+    let ten_years: u64 = 10 * 365 * 24 * 60 * 60;
+    client.deposit(&beneficiary, &1_000_000, &0, &ten_years, &ten_years, &false);
+    let ttl = |key: &DataKey| {
+        env.as_contract(&client.address, || env.storage().persistent().get_ttl(key))
+    };
+
+    // Nothing can be released before the cliff, so only permissionless
+    // bumps every 160 days keep the schedule alive.
+    let mut sequence = 0;
+    while (sequence as u64) * 5 < ten_years {
+        sequence += 160 * LEDGERS_PER_DAY;
+        env.ledger().set_sequence_number(sequence);
+        env.ledger().set_timestamp(sequence as u64 * 5);
+        assert!(ttl(&schedule_key) > 0);
+        assert!(ttl(&count_key) > 0);
+        client.bump_schedules(&beneficiary);
+        assert_eq!(ttl(&schedule_key), TTL_EXTEND_TO);
+        assert_eq!(ttl(&count_key), TTL_EXTEND_TO);
+    }
+
+    assert_eq!(client.release(&beneficiary, &0), 1_000_000);
+    assert_eq!(token_client.balance(&beneficiary), 1_000_000);
+}
+
+/// This crate built for `wasm32v1-none`, standing in for the next release;
+/// `cargo build --target wasm32v1-none --release` has to run first.
+mod release {
+    soroban_sdk::contractimport!(
+        file = "../../target/wasm32v1-none/release/vesting_contracts.wasm"
+    );
+}
+
+/// Executable recorded in the contract's instance entry.
+fn executable(env: &Env, contract: &Address) -> xdr::ContractExecutable {
+    let contract = xdr::ScAddress::from(contract);
+    env.to_ledger_snapshot()
+        .ledger_entries
+        .into_iter()
+        .find_map(|(_, (entry, _))| match entry.data {
+            xdr::LedgerEntryData::ContractData(data)
+                if data.contract == contract
+                    && data.key == xdr::ScVal::LedgerKeyContractInstance =>
+            {
+                match data.val {
+                    xdr::ScVal::ContractInstance(instance) => Some(instance.executable),
+                    _ => None,
+                }
+            }
+            _ => None,
+        })
+        .unwrap()
+}
+
+#[test]
+fn test_upgrade_and_migrate_require_owner() {
+    let env = Env::default();
+    let (client, _owner, _token) = setup(&env);
+    assert_eq!(client.schema_version(), SCHEMA_VERSION);
+    let hash = env.deployer().upload_contract_wasm(release::WASM);
+
+    env.set_auths(&[]);
+    assert!(client.try_upgrade(&hash).is_err());
+    assert!(client.try_migrate().is_err());
+}
+
+#[test]
+fn test_upgrade_and_migrate_keep_schedules() {
+    let env = Env::default();
+    let (client, owner, token) = setup(&env);
+    let token_client = token::Client::new(&env, &token);
+    let beneficiary = Address::generate(&env);
+    let treasury = client.treasury();
+    client.deposit(&beneficiary, &1_000, &0, &0, &1_000, &true);
+    let schedule = client.get_schedule(&beneficiary, &0);
+
+    let hash = env.deployer().upload_contract_wasm(release::WASM);
+    client.upgrade(&hash);
+    assert_eq!(
+        executable(&env, &client.address),
+        xdr::ContractExecutable::Wasm(xdr::Hash(hash.to_array()))
+    );
+    assert_eq!(client.migrate(), SCHEMA_VERSION);
+    assert_eq!(client.schema_version(), SCHEMA_VERSION);
+
+    // The new code reads the data the old code wrote.
+    assert_eq!(client.owner(), owner);
+    assert_eq!(client.treasury(), treasury);
+    assert_eq!(client.admin_delay(), ADMIN_DELAY);
+    assert_eq!(client.get_schedule(&beneficiary, &0), schedule);
+    env.ledger().set_timestamp(400);
+    assert_eq!(client.release(&beneficiary, &0), 400);
+    assert_eq!(token_client.balance(&beneficiary), 400);
+}
+
+#[test]
+fn test_ownership_handover_is_timelocked() {
+    let env = Env::default();
+    let (client, owner, _token) = setup(&env);
+    let successor = Address::generate(&env);
+    let typo = Address::generate(&env);
+    assert_eq!(client.admin_delay(), ADMIN_DELAY);
+    assert_eq!(client.try_accept_admin(), Err(Ok(Error::NoPendingAdmin)));
+    assert_eq!(
+        client.try_cancel_admin_proposal(),
+        Err(Ok(Error::NoPendingAdmin))
+    );
+
+    client.propose_admin(&typo);
+    client.cancel_admin_proposal();
+    assert_eq!(client.get_pending_admin(), None);
+
+    env.ledger().set_timestamp(500);
+    let pending = client.propose_admin(&successor);
+    assert_eq!(pending.effective_at, 500 + ADMIN_DELAY);
+    env.ledger().set_timestamp(499 + ADMIN_DELAY);
+    assert_eq!(
+        client.try_accept_admin(),
+        Err(Ok(Error::TimelockNotElapsed))
+    );
+    assert_eq!(client.owner(), owner);
+
+    env.ledger().set_timestamp(500 + ADMIN_DELAY);
+    client.accept_admin();
+    assert_eq!(client.owner(), successor);
+    assert_eq!(client.get_pending_admin(), None);
+}
+
+#[test]
+fn test_error_codes_match_errors_md() {
+    assert_eq!(Error::InsufficientBalance as u32, 2);
+    assert_eq!(Error::ScheduleNotFound as u32, 3);
+    assert_eq!(Error::InvalidAmount as u32, 5);
+    assert_eq!(Error::InvalidSchedule as u32, 11);
+    assert_eq!(Error::NoPendingAdmin as u32, 21);
+    assert_eq!(Error::TimelockNotElapsed as u32, 22);
+    assert_eq!(Error::NotRevocable as u32, 25);
+    assert_eq!(Error::TooManySchedules as u32, 26);
 }
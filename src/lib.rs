@@ -1,2 +0,0 @@
-#![allow(unexpected_cfgs)]
-#![no_std]